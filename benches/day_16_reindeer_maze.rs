@@ -0,0 +1,105 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use advent_of_code_2024::default_input;
+use advent_of_code_2024::day_16_reindeer_maze::ReindeerMaze;
+use advent_of_code_2024::grid::{Grid, Position};
+use advent_of_code_2024::input::read_input;
+use advent_of_code_2024::solution::Solution;
+
+/// A tile and the direction the reindeer is facing as (row, col, dy, dx).
+type Step = (i32, i32, i32, i32);
+
+/// Benchmark both parts of day 16 on the puzzle input, run with `cargo bench`.
+///
+/// Both parts are also compared against the search that kept a copy of the visited tiles for every path,
+/// which was replaced by a forward and a backward search.
+/// The input is read from $AOC_DATA_DIR, or `./data` when it is not set.
+fn reindeer_maze(c: &mut Criterion) {
+    let data = read_input(&default_input(&ReindeerMaze)).expect("the puzzle input in the data directory");
+    let input = ReindeerMaze::parse(&data).unwrap();
+
+    c.bench_function("day 16 lowest score", | b | b.iter(|| ReindeerMaze::part_one(&input)));
+    c.bench_function("day 16 tiles visited", | b | b.iter(|| ReindeerMaze::part_two(&input)));
+
+    let (map, start, end) = parse_maze(&data);
+    let answers = (ReindeerMaze::part_one(&input).unwrap(), ReindeerMaze::part_two(&input).unwrap());
+    assert_eq!(path_cloning_search(&map, start, end), answers, "both searches find the same answers");
+
+    let mut group = c.benchmark_group("day 16 both parts");
+    group.sample_size(10);
+    group.bench_function(
+        "forward and backward search",
+        | b | b.iter(|| (ReindeerMaze::part_one(&input).unwrap(), ReindeerMaze::part_two(&input).unwrap()))
+    );
+    group.bench_function("path cloning search", | b | b.iter(|| path_cloning_search(&map, start, end)));
+    group.finish();
+}
+
+/// The map with the start and end replaced by open tiles.
+fn parse_maze(data: &[u8]) -> (Grid<u8>, Position, Position) {
+    let mut map = Grid::parse_with(
+        data, "'#', '.', 'S' or 'E'", | char | matches!(char, b'#' | b'.' | b'S' | b'E').then_some(char)
+    ).unwrap();
+    let start = map.take_marker(b'S', b'.').unwrap();
+    let end = map.take_marker(b'E', b'.').unwrap();
+
+    (map, start, end)
+}
+
+/// Dijkstra that carries the tiles visited along with every step in the queue, as day 16 used to do.
+///
+/// Returns the lowest score and the number of tiles on the best paths.
+fn path_cloning_search(map: &Grid<u8>, start: Position, end: Position) -> (u32, usize) {
+    let mut visited: HashMap<Step, (Reverse<u32>, Vec<Position>)> = HashMap::new();
+    let mut queue: BinaryHeap<(Reverse<u32>, Step, Vec<Position>)> =
+        BinaryHeap::from([(Reverse(0), (start.0, start.1, 0, 1), Vec::new())]);
+    let mut best_score: u32 = u32::MAX;
+    let mut total_tiles_visited: usize = 0;
+
+    while let Some((current_cost, step, mut tiles_visited)) = queue.pop() {
+        if current_cost.0 > best_score {
+            continue
+        }
+
+        match visited.get(&step) {
+            Some((cost, _)) if *cost > current_cost => continue,
+            Some((cost, previous_steps)) if *cost == current_cost => {
+                tiles_visited.append(&mut previous_steps.clone());
+            },
+            _ => { visited.insert(step, (current_cost, tiles_visited.clone())); },
+        };
+
+        let (row, col, dy, dx) = step;
+        tiles_visited.push((row, col));
+
+        if (row, col) == end {
+            best_score = current_cost.0;
+
+            tiles_visited.sort_unstable();
+            tiles_visited.dedup();
+            total_tiles_visited = total_tiles_visited.max(tiles_visited.len());
+            continue
+        }
+
+        // Straight ahead, or turn 90 degrees and take a step.
+        let turns = match dy {
+            0 => [(-1, 0), (1, 0)],
+            _ => [(0, 1), (0, -1)]
+        };
+        let moves = [((dy, dx), 1)].into_iter().chain(turns.map(| turn | (turn, 1001)));
+
+        for ((dy, dx), cost) in moves {
+            if map.get((row + dy, col + dx)) == Some(&b'.') {
+                queue.push((Reverse(current_cost.0 + cost), (row + dy, col + dx, dy, dx), tiles_visited.clone()));
+            }
+        }
+    }
+
+    (best_score, total_tiles_visited)
+}
+
+criterion_group!(benches, reindeer_maze);
+criterion_main!(benches);
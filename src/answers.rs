@@ -0,0 +1,294 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::fs;
+
+use anyhow::{anyhow, bail, Result as Result};
+use serde::Deserialize;
+
+use crate::{find_solution, run_day, DayResult, InputSet, DEFAULT_SET, SOLUTIONS};
+use crate::solution::Puzzle;
+
+/// The default location of the expected answers.
+pub const ANSWERS_FILE: &str = "./answers.toml";
+
+/// The expected answer of each (day, part).
+pub type Answers = HashMap<(u8, u8), String>;
+
+/// The layout of the answers file, a list of [[answers]] tables.
+#[derive(Deserialize)]
+struct AnswersFile {
+    #[serde(default)]
+    answers: Vec<ExpectedAnswer>,
+}
+
+#[derive(Deserialize)]
+struct ExpectedAnswer {
+    /// The input set of the answer, the default set when omitted.
+    set: Option<String>,
+    day: u8,
+    part: u8,
+    expected: ExpectedValue,
+}
+
+/// Numeric answers may be written without quotes.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExpectedValue {
+    Text(String),
+    Number(i64),
+}
+
+/// The outcome of comparing an answer to the expected answer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String },
+    /// There is no expected answer for this part.
+    Missing,
+    /// The input could not be read or parsed, with the error.
+    Unsolved { error: String },
+}
+
+/// The verdict of a single part.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Check {
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+/// Solve a single day, or every day, for each input set and compare the answers to the answers file.
+///
+/// A day whose input can not be read or parsed gets an unsolved verdict for both parts, the other days are still checked.
+/// Returns an error when an answer is wrong or unsolved, missing answers are only reported.
+pub fn verify_days(day: Option<u8>, file: &str, sets: &[InputSet], parallel: bool) -> Result<()> {
+    let puzzles: Vec<&dyn Puzzle> = match day {
+        Some(day) => vec![find_solution(day).ok_or_else(|| anyhow!("Day {} has not been solved (yet)", day))?],
+        None => SOLUTIONS.to_vec()
+    };
+    let text = fs::read_to_string(file)?;
+
+    let mut checks: Vec<(String, Vec<Check>)> = Vec::new();
+    for set in sets.iter() {
+        let answers = parse_answers(&text, &set.name)?;
+        let mut set_checks: Vec<Check> = Vec::new();
+
+        for puzzle in puzzles.iter() {
+            let input = set.input(*puzzle);
+            match run_day(*puzzle, None, Some(&input), parallel) {
+                Ok(result) => set_checks.extend(check_answers(&[result], &answers)),
+                Err(error) => set_checks.extend(unsolved_checks(puzzle.day(), &format!("{}: {:#}", input, error))),
+            }
+        }
+
+        checks.push((set.name.clone(), set_checks));
+    }
+    print!("{}", format_checks(&checks));
+
+    let count = | verdict: fn(&Verdict) -> bool | {
+        checks.iter().flat_map(| (_, checks) | checks).filter(| check | verdict(&check.verdict)).count()
+    };
+    let failed = count(| verdict | matches!(verdict, Verdict::Fail { .. }));
+    let unsolved = count(| verdict | matches!(verdict, Verdict::Unsolved { .. }));
+    match (failed, unsolved) {
+        (0, 0) => Ok(()),
+        (_, 0) => bail!("{} answer(s) do not match {}", failed, file),
+        _ => bail!("{} answer(s) do not match {} and {} could not be solved", failed, file, unsolved)
+    }
+}
+
+/// Read the expected answers of a single input set.
+pub fn read_answers(file: &str, set: &str) -> Result<Answers> {
+    parse_answers(&fs::read_to_string(file)?, set)
+}
+
+/// Parse the [[answers]] tables with a day, part and expected answer, and keep those of a single input set.
+///
+/// Every (day, part) may only occur once per set.
+pub fn parse_answers(text: &str, set: &str) -> Result<Answers> {
+    let mut answers = Answers::new();
+
+    for answer in toml::from_str::<AnswersFile>(text)?.answers {
+        if answer.set.as_deref().unwrap_or(DEFAULT_SET) != set {
+            continue
+        }
+        if !(1..=2).contains(&answer.part) {
+            bail!("Day {} has no part {}", answer.day, answer.part);
+        }
+
+        let expected = match answer.expected {
+            ExpectedValue::Text(text) => text,
+            ExpectedValue::Number(number) => number.to_string(),
+        };
+        if answers.insert((answer.day, answer.part), expected).is_some() {
+            bail!("Day {} part {} has more than one expected answer", answer.day, answer.part);
+        }
+    }
+
+    Ok(answers)
+}
+
+/// Compare every answer of the results to the expected answers.
+pub fn check_answers(results: &[DayResult], answers: &Answers) -> Vec<Check> {
+    results
+        .iter()
+        .flat_map(
+            | result | {
+                result.solved.answers.iter().map(
+                    | answer | {
+                        let verdict = match answers.get(&(result.day, answer.part)) {
+                            Some(expected) if *expected == answer.value => Verdict::Pass,
+                            Some(expected) => Verdict::Fail { expected: expected.clone() },
+                            None => Verdict::Missing,
+                        };
+
+                        Check { day: result.day, part: answer.part, answer: answer.value.clone(), verdict }
+                    }
+                )
+            }
+        )
+        .collect()
+}
+
+/// Both parts of a day that could not be solved.
+fn unsolved_checks(day: u8, error: &str) -> [Check; 2] {
+    [1, 2].map(| part | Check { day, part, answer: String::new(), verdict: Verdict::Unsolved { error: error.to_string() } })
+}
+
+/// Format a line per part and a summary of the verdicts of each input set.
+///
+/// With multiple sets the verdicts of a part are shown side by side, prefixed by the name of the set.
+pub fn format_checks(sets: &[(String, Vec<Check>)]) -> String {
+    let named = sets.len() > 1;
+    let mut rows: BTreeMap<(u8, u8), Vec<String>> = BTreeMap::new();
+    let mut summary = String::new();
+
+    for (name, checks) in sets.iter() {
+        let mut counts: [usize; 4] = [0; 4];
+
+        for check in checks.iter() {
+            let verdict = match &check.verdict {
+                Verdict::Pass => {
+                    counts[0] += 1;
+                    "pass".to_string()
+                },
+                Verdict::Fail { expected } => {
+                    counts[1] += 1;
+                    format!("FAIL, expected {} but found {}", expected, check.answer)
+                },
+                Verdict::Missing => {
+                    counts[2] += 1;
+                    format!("missing, found {}", check.answer)
+                },
+                Verdict::Unsolved { error } => {
+                    counts[3] += 1;
+                    format!("UNSOLVED, {}", error)
+                },
+            };
+
+            let row = rows.entry((check.day, check.part)).or_default();
+            match named {
+                true => row.push(format!("{} {}", name, verdict)),
+                false => row.push(verdict),
+            }
+        }
+
+        if named {
+            write!(summary, "{}: ", name).unwrap();
+        }
+        writeln!(summary, "{} passed, {} failed, {} missing, {} unsolved", counts[0], counts[1], counts[2], counts[3]).unwrap();
+    }
+
+    let mut text = String::new();
+    for ((day, part), verdicts) in rows.iter() {
+        writeln!(text, "Day {} part {}: {}", day, part, verdicts.join(" | ")).unwrap();
+    }
+
+    format!("{}\n{}", text, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::solution::{Answer, Solved};
+
+    const EXAMPLE: &str =
+        "[[answers]]\n\
+        day = 1\n\
+        part = 1\n\
+        expected = \"11\"\n\
+        \n\
+        [[answers]]\n\
+        day = 1\n\
+        part = 2\n\
+        expected = 30\n\
+        \n\
+        [[answers]]\n\
+        set = \"alice\"\n\
+        day = 1\n\
+        part = 1\n\
+        expected = \"12\"\n";
+
+    #[test]
+    fn test_parse_answers() {
+        let answers = parse_answers(EXAMPLE, DEFAULT_SET).unwrap();
+        assert_eq!(answers.get(&(1, 1)).map(String::as_str), Some("11"));
+        assert_eq!(answers.get(&(1, 2)).map(String::as_str), Some("30"));
+
+        let answers = parse_answers(EXAMPLE, "alice").unwrap();
+        assert_eq!(answers.get(&(1, 1)).map(String::as_str), Some("12"));
+        assert_eq!(answers.len(), 1);
+
+        assert!(parse_answers(&EXAMPLE.replace("part = 2", "part = 1"), DEFAULT_SET).is_err());
+        assert!(parse_answers(&EXAMPLE.replace("part = 2", "part = 3"), DEFAULT_SET).is_err());
+        assert!(parse_answers("[[answers]]\nday = 1\n", DEFAULT_SET).is_err());
+        assert!(parse_answers("", DEFAULT_SET).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_check_answers() {
+        let answer = | part, value: &str | Answer { part, label: "Label", value: value.to_string(), elapsed: Duration::ZERO };
+        let results = vec![
+            DayResult {
+                day: 1,
+                title: "Historian Hysteria",
+                elapsed: Duration::ZERO,
+                solved: Solved { parse_time: Duration::ZERO, answers: vec![answer(1, "11"), answer(2, "31")] }
+            },
+            DayResult {
+                day: 2,
+                title: "Red-Nosed Reports",
+                elapsed: Duration::ZERO,
+                solved: Solved { parse_time: Duration::ZERO, answers: vec![answer(2, "4")] }
+            },
+        ];
+
+        let checks = check_answers(&results, &parse_answers(EXAMPLE, DEFAULT_SET).unwrap());
+        let verdicts: Vec<&Verdict> = checks.iter().map(| check | &check.verdict).collect();
+        assert_eq!(verdicts, vec![&Verdict::Pass, &Verdict::Fail { expected: "30".to_string() }, &Verdict::Missing]);
+        assert_eq!(
+            format_checks(&[(DEFAULT_SET.to_string(), checks.clone())]),
+            "Day 1 part 1: pass\n\
+            Day 1 part 2: FAIL, expected 30 but found 31\n\
+            Day 2 part 2: missing, found 4\n\
+            \n\
+            1 passed, 1 failed, 1 missing, 0 unsolved\n"
+        );
+
+        let mut alice = check_answers(&results[..1], &parse_answers(EXAMPLE, "alice").unwrap());
+        alice.extend(unsolved_checks(2, "line 1, column 2: expected a number, found \"x\""));
+        assert_eq!(
+            format_checks(&[(DEFAULT_SET.to_string(), checks), ("alice".to_string(), alice)]),
+            "Day 1 part 1: default pass | alice FAIL, expected 12 but found 11\n\
+            Day 1 part 2: default FAIL, expected 30 but found 31 | alice missing, found 31\n\
+            Day 2 part 1: alice UNSOLVED, line 1, column 2: expected a number, found \"x\"\n\
+            Day 2 part 2: default missing, found 4 | alice UNSOLVED, line 1, column 2: expected a number, found \"x\"\n\
+            \n\
+            default: 1 passed, 1 failed, 1 missing, 0 unsolved\n\
+            alice: 0 passed, 1 failed, 1 missing, 2 unsolved\n"
+        );
+    }
+}
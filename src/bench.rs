@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result as Result};
+
+use crate::input::read_input;
+use crate::parse_error::{parse_field, ParseError};
+use crate::solution::Puzzle;
+use crate::{find_solution, InputSet, SOLUTIONS};
+
+const BASELINE_HEADER: &str = "day,phase,median_ns";
+
+/// How to run the benchmarks.
+#[derive(Clone, Debug)]
+pub struct BenchOptions {
+    /// The puzzle inputs to benchmark on.
+    pub inputs: InputSet,
+    /// Number of measured runs per day.
+    pub runs: usize,
+    /// Number of runs before measuring, to warm up the caches.
+    pub warmup: usize,
+    /// Save the medians to this file, to compare against later.
+    pub save: Option<String>,
+    /// Compare the medians against this file.
+    pub baseline: Option<String>,
+    /// A phase is a regression when its median is this percentage slower than the baseline.
+    pub threshold: f64,
+    /// Let the days that can use multiple threads solve on the thread pool.
+    pub parallel: bool,
+}
+
+/// Statistics of the measured run times of a single phase.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    /// The sample standard deviation, zero for a single run.
+    pub stddev: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+
+        let n = sorted.len();
+        let median = match n % 2 {
+            0 => (sorted[n / 2 - 1] + sorted[n / 2]) / 2,
+            _ => sorted[n / 2]
+        };
+
+        let mean = sorted.iter().sum::<Duration>() / n as u32;
+        let variance = match n {
+            1 => 0.,
+            _ => {
+                sorted
+                    .iter()
+                    .map(| sample | (sample.as_nanos() as f64 - mean.as_nanos() as f64).powi(2))
+                    .sum::<f64>() / (n - 1) as f64
+            }
+        };
+
+        Self { min: sorted[0], median, mean, stddev: Duration::from_nanos(variance.sqrt().round() as u64) }
+    }
+}
+
+/// The statistics of each phase of a day: "parse", "part 1" and "part 2".
+#[derive(Clone, Debug)]
+pub struct BenchResult {
+    pub day: u8,
+    pub title: &'static str,
+    pub runs: usize,
+    pub phases: Vec<(String, Stats)>,
+}
+
+/// The median of each (day, phase) of an earlier run.
+pub type Baseline = HashMap<(u8, String), Duration>;
+
+/// Benchmark a single day, or every day, print the statistics and compare them to the baseline.
+///
+/// Returns an error when a phase is slower than the baseline allows, so scripts can detect it.
+pub fn bench_days(day: Option<u8>, options: &BenchOptions) -> Result<()> {
+    let puzzles: Vec<&dyn Puzzle> = match day {
+        Some(day) => vec![find_solution(day).ok_or_else(|| anyhow!("Day {} has not been solved (yet)", day))?],
+        None => SOLUTIONS.to_vec()
+    };
+    let baseline = options.baseline.as_deref().map(read_baseline).transpose()?;
+
+    let mut results: Vec<BenchResult> = Vec::new();
+    let mut regressions: usize = 0;
+    for puzzle in puzzles {
+        let data = read_input(&options.inputs.input(puzzle))?;
+        let result = bench_day(puzzle, &data, options.runs, options.warmup, options.parallel)?;
+
+        let comparison = baseline.as_ref().map(| baseline | compare(&result, baseline, options.threshold));
+        regressions += comparison.iter().flatten().flatten().filter(| (_, regression) | *regression).count();
+        println!("{}", format_bench(&result, comparison.as_deref()));
+
+        results.push(result);
+    }
+
+    if let Some(file) = &options.save {
+        fs::write(file, format_baseline(&results))?;
+    }
+
+    match regressions {
+        0 => Ok(()),
+        _ => bail!("{} phase(s) slower than the baseline", regressions)
+    }
+}
+
+/// Solve the day `warmup + runs` times and only measure the last runs.
+///
+/// The input is read once beforehand, so only parsing and solving are timed.
+pub fn bench_day(puzzle: &dyn Puzzle, data: &[u8], runs: usize, warmup: usize, parallel: bool) -> Result<BenchResult> {
+    if runs == 0 {
+        bail!("at least one run is needed");
+    }
+
+    for _ in 0..warmup {
+        puzzle.solve(data, None, parallel)?;
+    }
+
+    let mut samples: Vec<(String, Vec<Duration>)> = Vec::new();
+    for _ in 0..runs {
+        let solved = puzzle.solve(data, None, parallel)?;
+        let phases = std::iter::once(("parse".to_string(), solved.parse_time))
+            .chain(solved.answers.iter().map(| answer | (format!("part {}", answer.part), answer.elapsed)));
+
+        for (i, (phase, elapsed)) in phases.enumerate() {
+            match samples.get_mut(i) {
+                Some((_, times)) => times.push(elapsed),
+                None => samples.push((phase, vec![elapsed])),
+            }
+        }
+    }
+
+    Ok(
+        BenchResult {
+            day: puzzle.day(),
+            title: puzzle.title(),
+            runs,
+            phases: samples.into_iter().map(| (phase, times) | (phase, Stats::from_samples(&times))).collect(),
+        }
+    )
+}
+
+/// The change of the median of each phase compared to the baseline in percent, and whether that is a regression.
+///
+/// Phases that are not in the baseline get no comparison.
+pub fn compare(result: &BenchResult, baseline: &Baseline, threshold: f64) -> Vec<Option<(f64, bool)>> {
+    result.phases
+        .iter()
+        .map(
+            | (phase, stats) | {
+                let previous = baseline.get(&(result.day, phase.clone()))?;
+                let change = 100. * (stats.median.as_secs_f64() / previous.as_secs_f64().max(1e-9) - 1.);
+                Some((change, change > threshold))
+            }
+        )
+        .collect()
+}
+
+/// Format the statistics of a day as text, with a line per phase.
+pub fn format_bench(result: &BenchResult, comparison: Option<&[Option<(f64, bool)>]>) -> String {
+    let mut text = format!("Day {}: {} ({} runs)\n", result.day, result.title, result.runs);
+
+    for (i, (phase, stats)) in result.phases.iter().enumerate() {
+        write!(
+            text, "{:<6}  min {:>10.1?}  median {:>10.1?}  mean {:>10.1?}  stddev {:>10.1?}",
+            phase, stats.min, stats.median, stats.mean, stats.stddev
+        ).unwrap();
+
+        match comparison.and_then(| comparison | comparison[i]) {
+            Some((change, true)) => write!(text, "  {:+.1}% REGRESSION", change).unwrap(),
+            Some((change, false)) => write!(text, "  {:+.1}%", change).unwrap(),
+            None => {}
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+/// Format the medians as CSV, with a row per phase.
+pub fn format_baseline(results: &[BenchResult]) -> String {
+    let mut csv = format!("{}\n", BASELINE_HEADER);
+
+    for result in results.iter() {
+        for (phase, stats) in result.phases.iter() {
+            writeln!(csv, "{},{},{}", result.day, phase, stats.median.as_nanos()).unwrap();
+        }
+    }
+
+    csv
+}
+
+fn read_baseline(file: &str) -> Result<Baseline> {
+    Ok(parse_baseline(&fs::read_to_string(file)?)?)
+}
+
+/// Parse the CSV written by `format_baseline`, the header is optional.
+pub fn parse_baseline(text: &str) -> Result<Baseline, ParseError> {
+    let mut baseline = Baseline::new();
+
+    for (i, line) in text.lines().enumerate() {
+        if line.is_empty() || line == BASELINE_HEADER {
+            continue
+        }
+
+        let fields: Vec<&str> = line.split(',').collect();
+        let [day, phase, median] = fields[..] else {
+            return Err(ParseError::in_line(i, line, line, "a row like '6,part 2,123456'"));
+        };
+
+        let day: u8 = parse_field(i, line, day, "a day")?;
+        let median: u64 = parse_field(i, line, median, "a median in nanoseconds")?;
+        baseline.insert((day, phase.to_string()), Duration::from_nanos(median));
+    }
+
+    Ok(baseline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_result() -> BenchResult {
+        let millis = | ms: &[u64] | Stats::from_samples(&ms.iter().map(| &ms | Duration::from_millis(ms)).collect::<Vec<_>>());
+        BenchResult {
+            day: 6,
+            title: "Guard Gallivant",
+            runs: 3,
+            phases: vec![
+                ("parse".to_string(), millis(&[1, 1, 1])),
+                ("part 1".to_string(), millis(&[6, 2, 4])),
+                ("part 2".to_string(), millis(&[30, 20, 25])),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = example_result().phases[1].1;
+        assert_eq!(stats.min, Duration::from_millis(2));
+        assert_eq!(stats.median, Duration::from_millis(4));
+        assert_eq!(stats.mean, Duration::from_millis(4));
+        assert_eq!(stats.stddev, Duration::from_millis(2));
+
+        let stats = Stats::from_samples(&[Duration::from_millis(7), Duration::from_millis(8)]);
+        assert_eq!((stats.median, stats.mean), (Duration::from_micros(7500), Duration::from_micros(7500)));
+
+        let stats = Stats::from_samples(&[Duration::from_millis(7)]);
+        assert_eq!((stats.median, stats.stddev), (Duration::from_millis(7), Duration::ZERO));
+    }
+
+    #[test]
+    fn test_baseline() {
+        let result = example_result();
+        let text = format_baseline(std::slice::from_ref(&result));
+        assert_eq!(text, "day,phase,median_ns\n6,parse,1000000\n6,part 1,4000000\n6,part 2,25000000\n");
+
+        let mut baseline = parse_baseline(&text).unwrap();
+        baseline.insert((6, "part 1".to_string()), Duration::from_millis(2));
+        baseline.remove(&(6, "part 2".to_string()));
+        assert_eq!(compare(&result, &baseline, 10.), vec![Some((0., false)), Some((100., true)), None]);
+
+        let error = parse_baseline("day,phase,median_ns\n6,part 1\n").err().unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        let error = parse_baseline("6,part 1,4ms\n").err().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (1, 10, "4ms"));
+    }
+}
@@ -1,94 +1,418 @@
 use std::collections::HashMap;
-use std::fs;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::io::BufRead;
+use std::str::FromStr;
 
-use anyhow::Result as Result;
+use anyhow::{bail, Result as Result};
 
-pub fn solve_day_01(file: &str) -> Result<(u32, u32)> {
-    let data = fs::read_to_string(file)?;
+use crate::input::open_input;
+use crate::parse_error::ParseError;
+use crate::solution::Solution;
 
-    // Store the 2 distance columns into two separate vectors.
-    let mut distances_left: Vec<u32> = Vec::new();
-    let mut distances_right: Vec<u32> = Vec::new();
-    for line in data.lines() {
-        if let Some((left, right)) = line.split_once("   ") {
-            distances_left.push(left.parse()?);
-            distances_right.push(right.parse()?);
+pub struct HistorianHysteria;
+
+impl Solution for HistorianHysteria {
+    type Input = (Vec<u64>, Vec<u64>);
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    const DAY: u8 = 1;
+    const TITLE: &'static str = "Historian Hysteria";
+    const LABELS: [&'static str; 2] = ["Sum of Distances", "Similarity Score"];
+    const INPUT_FILE: &'static str = "day_01_historian_hysteria.txt";
+
+    /// Store the 2 distance columns into two separate vectors.
+    fn parse(data: &[u8]) -> Result<Self::Input> {
+        two_columns(parse_columns(data)?)
+    }
+
+    /// The input is streamed, so it does not have to fit in memory as text.
+    fn parse_reader(reader: &mut dyn BufRead) -> Result<Self::Input> {
+        two_columns(parse_columns(reader)?)
+    }
+
+    /// An Overflow error when the answer does not fit in a u64.
+    fn part_one((distances_left, distances_right): &Self::Input) -> Result<u64> {
+        Ok(determine_sum_of_differences(&mut distances_left.clone(), &mut distances_right.clone())?)
+    }
+
+    /// An Overflow error when the answer does not fit in a u64.
+    fn part_two((distances_left, distances_right): &Self::Input) -> Result<u64> {
+        Ok(determine_similarity_score(distances_left, distances_right)?)
+    }
+}
+
+/// The input is streamed, so it does not have to fit in memory as text.
+pub fn solve_day_01(file: &str) -> Result<(u64, u64)> {
+    solve_checked(two_columns(read_columns(file)?)?)
+}
+
+pub fn solve_from_str(data: &str) -> Result<(u64, u64)> {
+    solve_checked(two_columns(parse_columns(data.as_bytes())?)?)
+}
+
+/// Solve both parts in any integer type, returning an error when an answer does not fit.
+pub fn solve_checked<T: Distance>((mut distances_left, mut distances_right): (Vec<T>, Vec<T>)) -> Result<(T, T)> {
+    let similarity_score = determine_similarity_score(&distances_left, &distances_right)?;
+    let sum_of_differences = determine_sum_of_differences(&mut distances_left, &mut distances_right)?;
+
+    Ok((sum_of_differences, similarity_score))
+}
+
+/// The unsigned integer types the distances can be computed in.
+pub trait Distance: Copy + Ord + Hash + FromStr + TryFrom<usize> {
+    const ZERO: Self;
+
+    fn abs_diff(self, other: Self) -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_distance {
+    ($($int:ty),*) => {
+        $(
+            impl Distance for $int {
+                const ZERO: Self = 0;
+
+                fn abs_diff(self, other: Self) -> Self {
+                    <$int>::abs_diff(self, other)
+                }
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$int>::checked_add(self, other)
+                }
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$int>::checked_mul(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_distance!(u32, u64, u128);
+
+/// A sum or product that does not fit in the integer type.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Overflow;
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the answer does not fit in the integer type, use a larger one")
+    }
+}
+
+impl Error for Overflow {}
+
+/// Lines that do not have the same numbers as the other lines, the puzzle needs every line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SkippedLines(pub Vec<ParseError>);
+
+impl Display for SkippedLines {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} line(s) do not match the other lines", self.0.len())?;
+        self.0.iter().try_for_each(| error | write!(f, "; {}", error))
+    }
+}
+
+impl Error for SkippedLines {}
+
+/// The columns of numbers in the input, and the lines that were skipped.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Columns<T> {
+    pub columns: Vec<Vec<T>>,
+    /// Why each skipped line does not match the other lines.
+    pub skipped: Vec<ParseError>,
+}
+
+/// Stream the columns of numbers from a file, `-` for stdin.
+pub fn read_columns<T: Distance>(file: &str) -> Result<Columns<T>> {
+    parse_columns(open_input(file)?)
+}
+
+/// Parse lines of numbers separated by any whitespace into columns, reading a single line at a time.
+///
+/// The first line with only numbers sets the number of columns.
+/// Lines with another number of fields, or a field that is not a number of type T, are skipped and reported,
+/// so a caller can decide whether to continue without them. Blank lines are ignored.
+pub fn parse_columns<T: Distance, R: BufRead>(mut reader: R) -> Result<Columns<T>> {
+    let mut columns: Columns<T> = Columns { columns: Vec::new(), skipped: Vec::new() };
+    let mut buffer = String::new();
+    let mut fields: Vec<T> = Vec::new();
+
+    for i in 0.. {
+        buffer.clear();
+        if reader.read_line(&mut buffer)? == 0 {
+            break
+        }
+
+        let line = buffer.trim_end_matches(['\n', '\r']);
+        if line.trim().is_empty() {
+            continue
+        }
+
+        fields.clear();
+        let mut skip: Option<ParseError> = None;
+        for field in line.split_whitespace() {
+            match field.parse::<T>() {
+                Ok(number) => fields.push(number),
+                Err(_) => {
+                    skip = Some(ParseError::in_line(i, line, field, "a number"));
+                    break
+                }
+            }
         }
+
+        if skip.is_none() && !columns.columns.is_empty() && fields.len() != columns.columns.len() {
+            let expected = format!("{} numbers", columns.columns.len());
+            skip = Some(ParseError::in_line(i, line, line, &expected));
+        }
+
+        match skip {
+            Some(error) => columns.skipped.push(error),
+            None => {
+                if columns.columns.is_empty() {
+                    columns.columns = vec![Vec::new(); fields.len()];
+                }
+                columns.columns.iter_mut().zip(fields.iter()).for_each(| (column, &number) | column.push(number));
+            }
+        }
+    }
+
+    Ok(columns)
+}
+
+/// The puzzle has exactly 2 columns and every line must match.
+///
+/// Input with skipped lines is rejected instead of solved without them, the error lists every skipped line.
+fn two_columns<T>(columns: Columns<T>) -> Result<(Vec<T>, Vec<T>)> {
+    if !columns.skipped.is_empty() {
+        return Err(SkippedLines(columns.skipped).into())
     }
 
-    // Calculate the sum of differences.
-    let sum_of_distances = determine_sum_of_differences(
-        &mut distances_left, &mut distances_right
-    );
+    match <[Vec<T>; 2]>::try_from(columns.columns) {
+        Ok([left, right]) => Ok((left, right)),
+        Err(columns) if columns.is_empty() => Ok((Vec::new(), Vec::new())),
+        Err(columns) => bail!("expected 2 columns, found {}", columns.len()),
+    }
+}
 
-    // Calculate the similarity score.
-    let similarity_score = determine_similarity_score(distances_left, distances_right);
+/// The sum of differences of every pair of columns, entry [i][j] compares column i with column j.
+///
+/// Each column is sorted once, the matrix is symmetric with zeros on the diagonal.
+pub fn distance_matrix<T: Distance>(columns: &[Vec<T>]) -> Result<Vec<Vec<T>>, Overflow> {
+    let sorted: Vec<Vec<T>> = columns
+        .iter()
+        .map(
+            | column | {
+                let mut column = column.clone();
+                column.sort_unstable();
+                column
+            }
+        )
+        .collect();
 
-    Ok((sum_of_distances, similarity_score))
+    sorted
+        .iter()
+        .map(| left | sorted.iter().map(| right | sum_of_sorted_differences(left, right)).collect())
+        .collect()
+}
+
+/// The similarity score of every pair of columns, entry [i][j] scores column i against the counts in column j.
+///
+/// Each column is counted once, the matrix is not symmetric.
+pub fn similarity_matrix<T: Distance>(columns: &[Vec<T>]) -> Result<Vec<Vec<T>>, Overflow> {
+    let counts: Vec<HashMap<T, T>> = columns
+        .iter()
+        .map(| column | count_distances(column))
+        .collect::<Result<_, _>>()?;
+
+    columns
+        .iter()
+        .map(| left | counts.iter().map(| counts | similarity_with_counts(left, counts)).collect())
+        .collect()
 }
 
 /// Determine the total sum of differences of two arrays.
 ///
 /// First, sort both arrays from low-to-high.
 /// Then iterate over both arrays, calculate the absolute difference, and return the sum.
-fn determine_sum_of_differences(distances_left: &mut[u32], distances_right: &mut[u32]) -> u32 {
+pub fn determine_sum_of_differences<T: Distance>(distances_left: &mut [T], distances_right: &mut [T]) -> Result<T, Overflow> {
     distances_left.sort_unstable();
     distances_right.sort_unstable();
 
+    sum_of_sorted_differences(distances_left, distances_right)
+}
+
+fn sum_of_sorted_differences<T: Distance>(distances_left: &[T], distances_right: &[T]) -> Result<T, Overflow> {
     distances_left
         .iter()
         .zip(distances_right)
-        .map(| (left, right) | left.abs_diff(*right))
-        .sum()
+        .try_fold(T::ZERO, | sum, (left, right) | sum.checked_add(left.abs_diff(*right)).ok_or(Overflow))
 }
 
 /// Determine the similarity score of two arrays.
 ///
 /// Count the occurrence of each number in the right array, and store in a map
 /// Iterate over the left array and take left_value * occurrence in right.
-fn determine_similarity_score(distances_left: Vec<u32>, distances_right: Vec<u32>) -> u32 {
-    // Create the map.
-    let mut distance_counts: HashMap<u32, u32> = HashMap::with_capacity(distances_right.len());
-    distances_right
+pub fn determine_similarity_score<T: Distance>(distances_left: &[T], distances_right: &[T]) -> Result<T, Overflow> {
+    similarity_with_counts(distances_left, &count_distances(distances_right)?)
+}
+
+fn similarity_with_counts<T: Distance>(distances_left: &[T], distance_counts: &HashMap<T, T>) -> Result<T, Overflow> {
+    distances_left
+        .iter()
+        .filter_map(| left | distance_counts.get(left).map(| right_count | left.checked_mul(*right_count)))
+        .try_fold(T::ZERO, | sum, score | sum.checked_add(score.ok_or(Overflow)?).ok_or(Overflow))
+}
+
+/// Count the occurrence of each number.
+fn count_distances<T: Distance>(distances: &[T]) -> Result<HashMap<T, T>, Overflow> {
+    let mut distance_counts: HashMap<T, usize> = HashMap::with_capacity(distances.len());
+    distances
         .iter()
         .for_each(
-            | right | {
+            | distance | {
                 distance_counts
-                    .entry(*right)
+                    .entry(*distance)
                     .and_modify(| value | *value += 1)
                     .or_insert(1);
             }
         );
 
-    // Calculate the similarity.
-    distances_left
-        .iter()
-        .filter_map(
-            | left | {
-                distance_counts
-                    .get(left)
-                    .map(| right_count | left * right_count)
-            }
-        )
-        .sum()
+    distance_counts
+        .into_iter()
+        .map(| (distance, count) | Ok((distance, T::try_from(count).map_err(|_| Overflow)?)))
+        .collect()
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::solution::Puzzle;
+    use crate::test_random::Random;
 
     #[test]
     fn test_determine_sum_of_differences() {
-        let mut distances_left = vec![3, 4, 2, 1, 3, 3];
-        let mut distances_right = vec![4, 3, 5, 3, 9, 3];
-        assert_eq!(determine_sum_of_differences(&mut distances_left, &mut distances_right), 11)
+        let mut distances_left: Vec<u32> = vec![3, 4, 2, 1, 3, 3];
+        let mut distances_right: Vec<u32> = vec![4, 3, 5, 3, 9, 3];
+        assert_eq!(determine_sum_of_differences(&mut distances_left, &mut distances_right), Ok(11))
     }
 
     #[test]
     fn test_determine_similarity_score() {
-        let distances_left = vec![3, 4, 2, 1, 3, 3];
-        let distances_right = vec![4, 3, 5, 3, 9, 3];
-        assert_eq!(determine_similarity_score(distances_left, distances_right), 31)
+        let distances_left: Vec<u32> = vec![3, 4, 2, 1, 3, 3];
+        let distances_right: Vec<u32> = vec![4, 3, 5, 3, 9, 3];
+        assert_eq!(determine_similarity_score(&distances_left, &distances_right), Ok(31))
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        let data = "3 4\t1\n4   3 2\n\n2 x 5\n1 3\n  3  9  3\r\n";
+        let columns = parse_columns::<u32, _>(data.as_bytes()).unwrap();
+        assert_eq!(columns.columns, vec![vec![3, 4, 3], vec![4, 3, 9], vec![1, 2, 3]]);
+
+        let skipped: Vec<(usize, usize, &str)> = columns.skipped
+            .iter()
+            .map(| error | (error.line, error.column, error.text.as_str()))
+            .collect();
+        assert_eq!(skipped, vec![(4, 3, "x"), (5, 1, "1 3")]);
+
+        assert_eq!(solve_from_str("3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n").unwrap(), (11, 31));
+        assert_eq!(solve_from_str("3 4\n4\t3\n").unwrap(), (0, 7));
+        assert!(solve_from_str("3 4 5\n").is_err());
+
+        let error = solve_from_str("3   4\n4   x\n2\n").unwrap_err().downcast::<SkippedLines>().unwrap();
+        assert_eq!(error.0.len(), 2);
+        assert_eq!(
+            error.to_string(),
+            "2 line(s) do not match the other lines; \
+            line 2, column 5: expected a number, found \"x\"; \
+            line 3, column 1: expected 2 numbers, found \"2\""
+        );
+
+        let mut reader: &[u8] = b"3   4\r\n4   3\r\n";
+        assert_eq!(HistorianHysteria::parse_reader(&mut reader).unwrap(), (vec![3, 4], vec![4, 3]));
+    }
+
+    #[test]
+    fn test_matrices() {
+        let columns: Vec<Vec<u32>> = vec![vec![3, 4, 2, 1, 3, 3], vec![4, 3, 5, 3, 9, 3], vec![3, 3, 3, 3, 3, 3]];
+        assert_eq!(distance_matrix(&columns), Ok(vec![vec![0, 11, 4], vec![11, 0, 9], vec![4, 9, 0]]));
+        assert_eq!(similarity_matrix(&columns), Ok(vec![vec![34, 31, 54], vec![31, 45, 54], vec![54, 54, 108]]));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let big = u32::MAX - 1;
+        assert_eq!(determine_sum_of_differences(&mut [0, 0], &mut [big, big]), Err(Overflow));
+        assert_eq!(determine_similarity_score(&[big], &[big, big]), Err(Overflow));
+        assert_eq!(determine_similarity_score(&[big as u64], &[big as u64, big as u64]), Ok(2 * big as u64));
+
+        let data = format!("0   {}\n0   {}\n", u64::MAX, u64::MAX);
+        assert!(solve_from_str(&data).unwrap_err().is::<Overflow>());
+        assert!(HistorianHysteria.solve(data.as_bytes(), Some(1), false).unwrap_err().is::<Overflow>());
+        assert_eq!(HistorianHysteria.solve(data.as_bytes(), Some(2), false).unwrap().answers[0].value, "0");
+        let columns = parse_columns::<u128, _>(data.as_bytes()).unwrap();
+        assert_eq!(distance_matrix(&columns.columns).unwrap()[0][1], 2 * u64::MAX as u128);
+    }
+
+    /// Sum the differences of the i-th smallest numbers, finding each by counting the smaller numbers.
+    fn naive_sum_of_differences(left: &[u32], right: &[u32]) -> u128 {
+        let nth_smallest = | numbers: &[u32], n: usize | -> u32 {
+            *numbers
+                .iter()
+                .find(
+                    | &&number | {
+                        let smaller = numbers.iter().filter(| &&other | other < number).count();
+                        let equal = numbers.iter().filter(| &&other | other == number).count();
+                        smaller <= n && n < smaller + equal
+                    }
+                )
+                .unwrap()
+        };
+
+        (0..left.len())
+            .map(| n | nth_smallest(left, n).abs_diff(nth_smallest(right, n)) as u128)
+            .sum()
+    }
+
+    /// Score every pair of numbers that are equal.
+    fn naive_similarity_score(left: &[u32], right: &[u32]) -> u128 {
+        left
+            .iter()
+            .map(| &l | right.iter().filter(| &&r | r == l).map(| &r | r as u128).sum::<u128>())
+            .sum()
+    }
+
+    #[test]
+    fn test_against_naive_reference() {
+        let mut random = Random::new(2024);
+
+        for case in 0..300 {
+            let length = random.below(40) as usize;
+            // Few distinct numbers for many matches, and numbers near u32::MAX to overflow a u32.
+            let (low, range) = match case % 3 {
+                0 => (0, 10),
+                1 => (0, 100_000),
+                _ => (u32::MAX as u64 - 1_000_000_000, 1_000_000_000),
+            };
+            let left: Vec<u32> = (0..length).map(|_| (low + random.below(range)) as u32).collect();
+            let right: Vec<u32> = (0..length).map(|_| (low + random.below(range)) as u32).collect();
+
+            let expected_sum = naive_sum_of_differences(&left, &right);
+            let expected_score = naive_similarity_score(&left, &right);
+            let expect = | expected: u128 | u32::try_from(expected).map_err(|_| Overflow);
+
+            assert_eq!(determine_sum_of_differences(&mut left.clone(), &mut right.clone()), expect(expected_sum));
+            assert_eq!(determine_similarity_score(&left, &right), expect(expected_score));
+
+            let wide = | numbers: &[u32] | numbers.iter().map(| &number | number as u128).collect::<Vec<u128>>();
+            let (left, right) = (wide(&left), wide(&right));
+            assert_eq!(solve_checked((left, right)).unwrap(), (expected_sum, expected_score));
+        }
     }
 }
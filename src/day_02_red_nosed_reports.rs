@@ -1,57 +1,283 @@
-use std::fs;
+use std::fmt::Write;
 
 use anyhow::Result as Result;
 
-pub fn solve_day_02(file: &str) -> Result<(u32, u32)> {
-    let data = fs::read_to_string(file)?;
-
-    let mut safe_rows_part_one: u32 = 0;
-    let mut safe_rows_part_two: u32 = 0;
-
-    for line in data.lines() {
-        let row: Vec<u8> = line
-            .split_ascii_whitespace()
-            .map(| val | val.parse::<u8>().unwrap())
-            .collect();
-
-        if is_report_safe(&row) {
-            safe_rows_part_one += 1;
-            safe_rows_part_two += 1;
-        } else if is_report_safe_with_single_error(&row) {
-            safe_rows_part_two += 1;
+use crate::input::read_input_to_string;
+use crate::parse_error::parse_field;
+use crate::solution::{DiagnoseOptions, Solution};
+
+type Reports = Vec<Vec<u8>>;
+
+pub struct RedNosedReports;
+
+impl Solution for RedNosedReports {
+    type Input = Reports;
+    type PartOne = u32;
+    type PartTwo = u32;
+
+    const DAY: u8 = 2;
+    const TITLE: &'static str = "Red-Nosed Reports";
+    const LABELS: [&'static str; 2] = ["Safe Reports", "Safe Reports"];
+    const INPUT_FILE: &'static str = "day_02_red_nosed_reports.txt";
+
+    fn parse(data: &[u8]) -> Result<Reports> {
+        let mut reports: Reports = Vec::new();
+
+        for (i, line) in std::str::from_utf8(data)?.lines().enumerate() {
+            let row: Vec<u8> = line
+                .split_ascii_whitespace()
+                .map(| val | parse_field(i, line, val, "a level"))
+                .collect::<Result<_, _>>()?;
+            reports.push(row);
         }
+
+        Ok(reports)
     }
 
-    Ok((safe_rows_part_one, safe_rows_part_two))
+    fn part_one(reports: &Reports) -> Result<u32> {
+        Ok(count_safe_reports(reports, &SafetyRules::PART_ONE))
+    }
+
+    fn part_two(reports: &Reports) -> Result<u32> {
+        Ok(count_safe_reports(reports, &SafetyRules::PART_TWO))
+    }
+
+    /// The status of every report under the given rules, or else the rules of part 2.
+    fn diagnostics(reports: &Reports, options: &DiagnoseOptions) -> Option<String> {
+        Some(format_diagnostics(reports, &options.safety_rules.unwrap_or(SafetyRules::PART_TWO)))
+    }
+}
+
+pub fn solve_day_02(file: &str) -> Result<(u32, u32)> {
+    let data = read_input_to_string(file)?;
+    solve_from_str(&data)
 }
 
-/// Check if an array is sorted from low-to-high or high-to-low
-/// With the constraint that the maximum difference between elements must be > 1 and <= 3
-/// The > 1 check is implied by checking a < b instead of using <=
-#[inline]
-fn is_report_safe(row: &[u8]) -> bool {
-    row.is_sorted_by(|a, b | a < b && a.abs_diff(*b) <= 3)
-        || row.is_sorted_by(| a, b| b < a && a.abs_diff(*b) <= 3)
+pub fn solve_from_str(data: &str) -> Result<(u32, u32)> {
+    RedNosedReports::solve_parts(data.as_bytes())
 }
 
-/// Check if an array is sorted while ignoring a single value.
-/// Uses is_report_safe while removing a single value from the array.
-#[inline]
-fn is_report_safe_with_single_error(row: &[u8]) -> bool {
-    for i in 0..row.len() {
-        let mut new_row = Vec::from(row);
-        new_row.remove(i);
-        if is_report_safe(&new_row) {
-            return true;
+/// Which way the levels of a safe report go.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    Increasing,
+    Decreasing,
+    /// Either increasing or decreasing, but the same for the whole report.
+    Either,
+}
+
+/// Why the levels of a pair break the rules.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Violation {
+    /// The levels go the other way than the rest of the report.
+    WrongDirection(Direction),
+    /// The difference is less than the minimal step.
+    StepTooSmall(u8),
+    /// The difference is more than the maximal step.
+    StepTooLarge(u8),
+}
+
+/// How a report fares under the rules.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReportStatus {
+    Safe,
+    /// Safe once the Problem Dampener removes the levels at these indices.
+    Dampened(Vec<usize>),
+    /// Unsafe, with the index of the first level of the first pair that breaks the rules.
+    Unsafe { index: usize, violation: Violation },
+}
+
+/// When a report counts as safe.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SafetyRules {
+    /// The smallest allowed difference between adjacent levels.
+    pub min_step: u8,
+    /// The largest allowed difference between adjacent levels.
+    pub max_step: u8,
+    pub direction: Direction,
+    /// The number of levels the Problem Dampener may remove.
+    pub dampener: usize,
+}
+
+impl SafetyRules {
+    /// Strictly increasing or decreasing in steps of 1 to 3.
+    pub const PART_ONE: Self = Self { min_step: 1, max_step: 3, direction: Direction::Either, dampener: 0 };
+    /// The same, but a single level may be removed.
+    pub const PART_TWO: Self = Self { dampener: 1, ..Self::PART_ONE };
+
+    pub fn is_safe(&self, report: &[u8]) -> bool {
+        self.explain(report).is_some()
+    }
+
+    /// The indices of the fewest levels to remove to make the report safe,
+    /// or None when the Problem Dampener would have to remove more levels than allowed.
+    pub fn explain(&self, report: &[u8]) -> Option<Vec<usize>> {
+        match self.direction {
+            Direction::Either => {
+                let increasing = self.removals(report, Direction::Increasing);
+                let decreasing = self.removals(report, Direction::Decreasing);
+                match (increasing, decreasing) {
+                    (Some(increasing), Some(decreasing)) if decreasing.len() < increasing.len() => Some(decreasing),
+                    (increasing, decreasing) => increasing.or(decreasing),
+                }
+            },
+            direction => self.removals(report, direction),
+        }
+    }
+
+    /// The status of a report, with the removed levels or the reason it is unsafe.
+    pub fn diagnose(&self, report: &[u8]) -> ReportStatus {
+        let Some((index, violation)) = self.first_violation(report) else {
+            return ReportStatus::Safe
+        };
+
+        match self.explain(report) {
+            Some(removed) => ReportStatus::Dampened(removed),
+            None => ReportStatus::Unsafe { index, violation },
         }
     }
 
-    false
+    /// The first pair of levels that breaks the rules without the Problem Dampener.
+    ///
+    /// When either direction is allowed, the first pair of different levels sets the direction of the report.
+    fn first_violation(&self, report: &[u8]) -> Option<(usize, Violation)> {
+        let direction = match self.direction {
+            Direction::Either => report
+                .windows(2)
+                .find(| pair | pair[0] != pair[1])
+                .map_or(Direction::Increasing, | pair | if pair[0] < pair[1] { Direction::Increasing } else { Direction::Decreasing }),
+            direction => direction,
+        };
+
+        report.windows(2).enumerate().find_map(
+            | (i, pair) | {
+                let step = pair[0].abs_diff(pair[1]);
+                let wrong_direction = match direction {
+                    Direction::Increasing => pair[1] < pair[0],
+                    _ => pair[0] < pair[1],
+                };
+
+                match step {
+                    _ if wrong_direction => Some((i, Violation::WrongDirection(direction))),
+                    step if step < self.min_step => Some((i, Violation::StepTooSmall(step))),
+                    step if step > self.max_step => Some((i, Violation::StepTooLarge(step))),
+                    _ => None,
+                }
+            }
+        )
+    }
+
+    fn is_valid_step(&self, from: u8, to: u8, direction: Direction) -> bool {
+        let step = match direction {
+            Direction::Increasing => to.checked_sub(from),
+            _ => from.checked_sub(to),
+        };
+        step.is_some_and(| step | (self.min_step..=self.max_step).contains(&step))
+    }
+
+    /// The fewest removals for a single direction, in O(n * dampener).
+    ///
+    /// With at most k removals, a kept level follows one of the k + 1 levels before it.
+    /// So for each level the fewest removals before it, when it is kept, follows from the k + 1 levels before.
+    fn removals(&self, report: &[u8], direction: Direction) -> Option<Vec<usize>> {
+        let n = report.len();
+        if n == 0 {
+            return Some(Vec::new())
+        }
+
+        // The fewest removals before each kept level, and the previous kept level.
+        let mut removed: Vec<Option<usize>> = vec![None; n];
+        let mut previous: Vec<Option<usize>> = vec![None; n];
+
+        for i in 0..n {
+            if i <= self.dampener {
+                removed[i] = Some(i);
+            }
+
+            for j in i.saturating_sub(self.dampener + 1)..i {
+                let Some(before) = removed[j] else { continue };
+                let total = before + i - j - 1;
+
+                if total <= self.dampener
+                    && removed[i].is_none_or(| current | total < current)
+                    && self.is_valid_step(report[j], report[i], direction) {
+                    removed[i] = Some(total);
+                    previous[i] = Some(j);
+                }
+            }
+        }
+
+        // The levels after the last kept level are removed as well.
+        let last = (n.saturating_sub(self.dampener + 1)..n)
+            .filter_map(| i | removed[i].map(| before | (before + n - 1 - i, i)))
+            .filter(| (total, _) | *total <= self.dampener)
+            .min()?
+            .1;
+
+        let mut kept: Vec<bool> = vec![false; n];
+        let mut current = Some(last);
+        while let Some(i) = current {
+            kept[i] = true;
+            current = previous[i];
+        }
+
+        Some((0..n).filter(| &i | !kept[i]).collect())
+    }
+}
+
+/// Format a line with the status of each report, followed by the number of reports of each status.
+pub fn format_diagnostics(reports: &Reports, rules: &SafetyRules) -> String {
+    let mut text = String::new();
+    let mut counts: [usize; 3] = [0; 3];
+
+    for (i, report) in reports.iter().enumerate() {
+        let levels: Vec<String> = report.iter().map(u8::to_string).collect();
+        write!(text, "Report {}: {}: ", i + 1, levels.join(" ")).unwrap();
+
+        match rules.diagnose(report) {
+            ReportStatus::Safe => {
+                counts[0] += 1;
+                writeln!(text, "safe").unwrap();
+            },
+            ReportStatus::Dampened(removed) => {
+                counts[1] += 1;
+                let removed: Vec<String> = removed
+                    .iter()
+                    .map(| &index | format!("index {} ({})", index, report[index]))
+                    .collect();
+                writeln!(text, "safe with dampener, removed {}", removed.join(", ")).unwrap();
+            },
+            ReportStatus::Unsafe { index, violation } => {
+                counts[2] += 1;
+                write!(text, "unsafe, {} -> {} at index {}: ", report[index], report[index + 1], index).unwrap();
+                match violation {
+                    Violation::WrongDirection(Direction::Increasing) => writeln!(text, "decreases in an increasing report"),
+                    Violation::WrongDirection(_) => writeln!(text, "increases in a decreasing report"),
+                    Violation::StepTooSmall(step) => writeln!(text, "step of {} is less than {}", step, rules.min_step),
+                    Violation::StepTooLarge(step) => writeln!(text, "step of {} is more than {}", step, rules.max_step),
+                }.unwrap();
+            },
+        }
+    }
+
+    writeln!(text, "\n{} safe, {} safe with dampener, {} unsafe", counts[0], counts[1], counts[2]).unwrap();
+    text
+}
+
+/// Count the reports that are safe according to the rules.
+pub fn count_safe_reports(reports: &Reports, rules: &SafetyRules) -> u32 {
+    reports
+        .iter()
+        .filter(| row | rules.is_safe(row))
+        .count() as u32
 }
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+
     use super::*;
+    use crate::test_random::Random;
 
     const ROW_1: [u8; 5] = [7, 6, 4, 2, 1];
     const ROW_2: [u8; 5] = [1, 2, 7, 8, 9];
@@ -61,6 +287,14 @@ mod tests {
     const ROW_6: [u8; 5] = [1, 3, 6, 7, 9];
     const ROW_7: [u8; 6] = [20, 17, 18, 21, 23, 25];
 
+    fn is_report_safe(row: &[u8]) -> bool {
+        SafetyRules::PART_ONE.is_safe(row)
+    }
+
+    fn is_report_safe_with_single_error(row: &[u8]) -> bool {
+        SafetyRules::PART_TWO.is_safe(row)
+    }
+
     #[test]
     fn test_check_safe_report_part_one() {
         assert!(is_report_safe(&ROW_1));
@@ -82,4 +316,104 @@ mod tests {
 
         assert!(is_report_safe_with_single_error(&ROW_7));
     }
+
+    #[test]
+    fn test_explain() {
+        assert_eq!(SafetyRules::PART_ONE.explain(&ROW_1), Some(vec![]));
+        assert_eq!(SafetyRules::PART_TWO.explain(&ROW_4), Some(vec![2]));
+        assert_eq!(SafetyRules::PART_TWO.explain(&ROW_5), Some(vec![3]));
+        assert_eq!(SafetyRules::PART_TWO.explain(&ROW_7), Some(vec![0]));
+        assert_eq!(SafetyRules::PART_TWO.explain(&ROW_2), None);
+
+        let two_removals = SafetyRules { dampener: 2, ..SafetyRules::PART_ONE };
+        assert_eq!(two_removals.explain(&ROW_2), Some(vec![0, 1]));
+        assert_eq!(two_removals.explain(&[5, 1, 6, 2, 7]), Some(vec![1, 3]));
+
+        let increasing = SafetyRules { direction: Direction::Increasing, ..SafetyRules::PART_TWO };
+        assert_eq!(increasing.explain(&ROW_1), None);
+        assert_eq!(increasing.explain(&[1, 2]), Some(vec![]));
+        assert_eq!(increasing.explain(&[2, 1]), Some(vec![1]));
+
+        let flat = SafetyRules { min_step: 0, max_step: 1, direction: Direction::Decreasing, dampener: 0 };
+        assert!(flat.is_safe(&[3, 3, 2, 2, 1]));
+        assert!(!flat.is_safe(&[3, 1]));
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let reports: Reports = [&ROW_1[..], &ROW_2, &ROW_3, &ROW_4, &ROW_5, &ROW_6].iter().map(| row | row.to_vec()).collect();
+        assert_eq!(
+            format_diagnostics(&reports, &SafetyRules::PART_TWO),
+            "Report 1: 7 6 4 2 1: safe\n\
+            Report 2: 1 2 7 8 9: unsafe, 2 -> 7 at index 1: step of 5 is more than 3\n\
+            Report 3: 9 7 6 2 1: unsafe, 6 -> 2 at index 2: step of 4 is more than 3\n\
+            Report 4: 1 3 2 4 5: safe with dampener, removed index 2 (2)\n\
+            Report 5: 8 6 4 4 1: safe with dampener, removed index 3 (4)\n\
+            Report 6: 1 3 6 7 9: safe\n\
+            \n\
+            2 safe, 2 safe with dampener, 2 unsafe\n"
+        );
+
+        let strict = SafetyRules::PART_ONE;
+        assert_eq!(strict.diagnose(&ROW_4), ReportStatus::Unsafe { index: 1, violation: Violation::WrongDirection(Direction::Increasing) });
+        assert_eq!(strict.diagnose(&ROW_5), ReportStatus::Unsafe { index: 2, violation: Violation::StepTooSmall(0) });
+        assert_eq!(strict.diagnose(&[4, 4, 3]), ReportStatus::Unsafe { index: 0, violation: Violation::StepTooSmall(0) });
+        assert_eq!(strict.diagnose(&[4, 3, 4]), ReportStatus::Unsafe { index: 1, violation: Violation::WrongDirection(Direction::Decreasing) });
+        let default = RedNosedReports::diagnostics(&reports, &DiagnoseOptions::default()).unwrap();
+        assert!(default.ends_with("2 safe, 2 safe with dampener, 2 unsafe\n"));
+        let options = DiagnoseOptions { safety_rules: Some(SafetyRules::PART_ONE) };
+        assert!(RedNosedReports::diagnostics(&reports, &options).unwrap().ends_with("2 safe, 0 safe with dampener, 4 unsafe\n"));
+    }
+
+    /// Try every way to remove up to `dampener` levels.
+    fn brute_force_removals(rules: &SafetyRules, report: &[u8]) -> Option<usize> {
+        let strict = SafetyRules { dampener: 0, ..*rules };
+        (0..=rules.dampener.min(report.len())).find(
+            | &count | {
+                (0..report.len()).combinations(count).any(
+                    | removed | {
+                        let rest: Vec<u8> = (0..report.len()).filter(| i | !removed.contains(i)).map(| i | report[i]).collect();
+                        [Direction::Increasing, Direction::Decreasing]
+                            .iter()
+                            .filter(| &&direction | rules.direction == Direction::Either || rules.direction == direction)
+                            .any(| &direction | rest.windows(2).all(| pair | strict.is_valid_step(pair[0], pair[1], direction)))
+                    }
+                )
+            }
+        )
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut random = Random::new(2024);
+
+        for _ in 0..2000 {
+            let rules = SafetyRules {
+                min_step: random.below(2) as u8,
+                max_step: 1 + random.below(4) as u8,
+                direction: [Direction::Increasing, Direction::Decreasing, Direction::Either][random.below(3) as usize],
+                dampener: random.below(4) as usize,
+            };
+            let start = 20 + random.below(20) as i16;
+            let report: Vec<u8> = (0..random.below(9))
+                .scan(start, | level, _ | {
+                    *level += random.below(9) as i16 - 4;
+                    Some(*level as u8)
+                })
+                .collect();
+
+            let explained = rules.explain(&report);
+            match rules.diagnose(&report) {
+                ReportStatus::Safe => assert_eq!(explained, Some(vec![])),
+                ReportStatus::Dampened(removed) => assert_eq!(explained, Some(removed)),
+                ReportStatus::Unsafe { .. } => assert_eq!(explained, None),
+            }
+            assert_eq!(explained.as_ref().map(Vec::len), brute_force_removals(&rules, &report), "{:?} {:?}", rules, report);
+
+            if let Some(removed) = explained {
+                let rest: Vec<u8> = (0..report.len()).filter(| i | !removed.contains(i)).map(| i | report[i]).collect();
+                assert!(SafetyRules { dampener: 0, ..rules }.is_safe(&rest));
+            }
+        }
+    }
 }
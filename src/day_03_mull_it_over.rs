@@ -1,10 +1,41 @@
-use std::fs;
 use regex::{Captures, Regex};
 use anyhow::Result as Result;
 
+use crate::input::read_input_to_string;
+use crate::solution::Solution;
+
+pub struct MullItOver;
+
+impl Solution for MullItOver {
+    type Input = String;
+    type PartOne = u32;
+    type PartTwo = u32;
+
+    const DAY: u8 = 3;
+    const TITLE: &'static str = "Mull It Over";
+    const LABELS: [&'static str; 2] = ["Sum of instructions", "Sum of instructions"];
+    const INPUT_FILE: &'static str = "day_03_mull_it_over.txt";
+
+    fn parse(data: &[u8]) -> Result<String> {
+        Ok(String::from_utf8(data.to_vec())?)
+    }
+
+    fn part_one(memory: &String) -> Result<u32> {
+        Ok(parse_mul_with_do(memory).0)
+    }
+
+    fn part_two(memory: &String) -> Result<u32> {
+        Ok(parse_mul_with_do(memory).1)
+    }
+}
+
 pub fn solve_day_03(file: &str) -> Result<(u32, u32)> {
-    let data = fs::read_to_string(file)?;
-    Ok(parse_mul_with_do(&data))
+    let data = read_input_to_string(file)?;
+    solve_from_str(&data)
+}
+
+pub fn solve_from_str(data: &str) -> Result<(u32, u32)> {
+    MullItOver::solve_parts(data.as_bytes())
 }
 
 /// Parse the mul values including the do and don't conditional statements.
@@ -51,11 +82,11 @@ mod tests {
 
     #[test]
     fn test_parse_mul() {
-        assert_eq!(parse_mul_with_do(EXAMPLE_ONE).0, 161);
+        assert_eq!(solve_from_str(EXAMPLE_ONE).unwrap().0, 161);
     }
 
     #[test]
     fn test_parse_mul_with_do() {
-        assert_eq!(parse_mul_with_do(EXAMPLE_TWO).1, 48);
+        assert_eq!(solve_from_str(EXAMPLE_TWO).unwrap().1, 48);
     }
 }
@@ -1,59 +1,111 @@
 use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::fs;
 
 use anyhow::Result as Result;
 
+use crate::input::read_input_to_string;
+use crate::parse_error::{parse_field, ParseError};
+use crate::solution::Solution;
+
 type OrderingMap = HashMap<u8, Vec<u8>>;
+type PageSets = Vec<Vec<u8>>;
 
-pub fn solve_day_05(file: &str) -> Result<(u32, u32)> {
-    let data = fs::read_to_string(file)?;
+pub struct PrintQueue;
 
-    // Split the data into the part with ordering_rules and pages.
-    let (ordering_rules, pages) = data.split_once("\n\n").unwrap();
+impl Solution for PrintQueue {
+    type Input = (OrderingMap, PageSets);
+    type PartOne = u32;
+    type PartTwo = u32;
 
-    let ordering_map = create_ordering_map(ordering_rules);
+    const DAY: u8 = 5;
+    const TITLE: &'static str = "Print Queue";
+    const LABELS: [&'static str; 2] = ["Ordered Pages Sum", "Unordered Pages Sum"];
+    const INPUT_FILE: &'static str = "day_05_print_queue.txt";
 
-    let mut ordered_page_sum: u32 = 0;
-    let mut unordered_page_sum: u32 = 0;
+    fn parse(data: &[u8]) -> Result<Self::Input> {
+        // Windows line endings would hide the empty line, the lines themselves do not mind them.
+        let data = std::str::from_utf8(data)?.replace("\r\n", "\n");
 
-    for line in pages.lines() {
-        let mut page_set: Vec<u8> = line
-            .split(',')
-            .map(|val| val.parse().unwrap())
-            .collect();
-
-        match check_order(&page_set, &ordering_map) {
-            // Ordered pages may be counted directly.
-            Some(page_number) => ordered_page_sum += page_number as u32,
-            // Unordered pages must be sorted and then counted for part two.
-            None => unordered_page_sum += sort_pages(&mut page_set, &ordering_map) as u32
-        }
+        // Split the data into the part with ordering_rules and pages.
+        let Some((ordering_rules, pages)) = data.split_once("\n\n") else {
+            let line_count = data.lines().count();
+            return Err(ParseError::new(line_count + 1, 1, "", "an empty line between rules and pages").into());
+        };
+
+        let ordering_map = create_ordering_map(ordering_rules)?;
+        let page_sets = create_page_sets(pages, ordering_rules.lines().count() + 1)?;
+
+        Ok((ordering_map, page_sets))
+    }
+
+    /// Ordered pages may be counted directly.
+    fn part_one((ordering_map, page_sets): &Self::Input) -> Result<u32> {
+        Ok(
+            page_sets
+                .iter()
+                .filter_map(| page_set | check_order(page_set, ordering_map))
+                .map(| page_number | page_number as u32)
+                .sum()
+        )
     }
 
-    Ok((ordered_page_sum, unordered_page_sum))
+    /// Unordered pages must be sorted and then counted.
+    fn part_two((ordering_map, page_sets): &Self::Input) -> Result<u32> {
+        Ok(
+            page_sets
+                .iter()
+                .filter(| page_set | check_order(page_set, ordering_map).is_none())
+                .map(| page_set | sort_pages(&mut page_set.clone(), ordering_map) as u32)
+                .sum()
+        )
+    }
+}
+
+pub fn solve_day_05(file: &str) -> Result<(u32, u32)> {
+    let data = read_input_to_string(file)?;
+    solve_from_str(&data)
+}
+
+pub fn solve_from_str(data: &str) -> Result<(u32, u32)> {
+    PrintQueue::solve_parts(data.as_bytes())
 }
 
 /// Create a map for a | b rules. Because a may occur multiple times create a | Vec<b's>.
-fn create_ordering_map(lines: &str) -> OrderingMap {
+fn create_ordering_map(lines: &str) -> Result<OrderingMap, ParseError> {
     let mut ordering_map: OrderingMap = HashMap::new();
-    lines
-        .lines()
-        .for_each(
-            | line | {
-                let (left, right) = match line.split_once('|') {
-                    Some((l, r)) => (l.parse().unwrap(), r.parse().unwrap()),
-                    None => unreachable!()
-                };
-
-                ordering_map
-                    .entry(left)
-                    .and_modify(| val | val.push(right))
-                    .or_insert(vec![right]);
-            }
-        );
 
-    ordering_map
+    for (i, line) in lines.lines().enumerate() {
+        let (left, right) = match line.split_once('|') {
+            Some((l, r)) => (
+                parse_field(i, line, l, "a page number")?,
+                parse_field(i, line, r, "a page number")?
+            ),
+            None => return Err(ParseError::in_line(i, line, line, "a rule like '47|53'"))
+        };
+
+        ordering_map
+            .entry(left)
+            .and_modify(| val | val.push(right))
+            .or_insert(vec![right]);
+    }
+
+    Ok(ordering_map)
+}
+
+/// Create the comma separated page sets, the first line is at line_offset of the input.
+fn create_page_sets(lines: &str, line_offset: usize) -> Result<PageSets, ParseError> {
+    let mut page_sets: PageSets = Vec::new();
+
+    for (i, line) in lines.lines().enumerate() {
+        let page_set: Vec<u8> = line
+            .split(',')
+            .map(| val | parse_field(i + line_offset, line, val, "a page number"))
+            .collect::<Result<_, _>>()?;
+
+        page_sets.push(page_set);
+    }
+
+    Ok(page_sets)
 }
 
 /// Check if the pages are ordered according to the OrderingMap.
@@ -138,7 +190,7 @@ mod tests {
 
     #[test]
     fn test_page_ordering() {
-        let ordering_map = create_ordering_map(MAP);
+        let ordering_map = create_ordering_map(MAP).unwrap();
 
         assert_eq!(check_order(&EXAMPLE1, &ordering_map), Some(61));
         assert_eq!(check_order(&EXAMPLE2, &ordering_map), Some(53));
@@ -150,7 +202,7 @@ mod tests {
 
     #[test]
     fn test_page_sorting() {
-        let ordering_map = create_ordering_map(MAP);
+        let ordering_map = create_ordering_map(MAP).unwrap();
 
         let mut ex = EXAMPLE4;
         assert_eq!(sort_pages(&mut ex, &ordering_map), 47);
@@ -161,4 +213,21 @@ mod tests {
         let mut ex = EXAMPLE6;
         assert_eq!(sort_pages(&mut ex, &ordering_map), 47);
     }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = create_ordering_map("47|53\n97-13\n").err().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 1, "97-13"));
+
+        let error = PrintQueue::parse(b"47|53\n97|13\n\n75,47\n97,x1,53\n").err().unwrap();
+        let error = error.downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (5, 4, "x1"));
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let (ordering_map, page_sets) = PrintQueue::parse(b"47|53\r\n97|13\r\n\r\n75,47,61\r\n97,13\r\n").unwrap();
+        assert_eq!(ordering_map.get(&97), Some(&vec![13]));
+        assert_eq!(page_sets, vec![vec![75, 47, 61], vec![97, 13]]);
+    }
 }
@@ -1,78 +1,141 @@
 use std::collections::{HashMap, HashSet};
-use std::fs;
 
-use anyhow::Result as Result;
+use anyhow::{bail, Result as Result};
 
-type LabMap = HashMap<(i32, i32), Tile>;
+use crate::grid::{Grid, Position, DIRECTIONS_4};
+use crate::input::read_input;
+use crate::map_items;
+use crate::parse_error::ParseError;
+use crate::solution::Solution;
+
+type LabMap = Grid<Tile>;
 type Start = ((i32, i32), (i32, i32));  // (coordinates, direction)
+type ObstacleHit = ((i32, i32), (i32, i32));  // (obstacle, direction the guard walked into it)
+
+/// For each tile and direction (as index in DIRECTIONS_4) the tile where the guard stops in front of the next
+/// obstacle, None if the guard walks out of the lab first.
+type JumpTable = Grid<[Option<Position>; 4]>;
 
 /// Positions in the Map.
-#[derive(Eq, PartialEq)]
-enum Tile {
+#[derive(Clone, Eq, PartialEq)]
+pub enum Tile {
     Open,
     Obstacle
 }
 
 /// Possible routes in the lab.
-enum Route {
-    Looping,
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Route {
+    Looping(Cycle),
     Finite
 }
 
+/// The part of a looping route that repeats forever.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cycle {
+    /// Where the guard enters the cycle, the coordinates and direction just before the first obstacle hit.
+    pub entry: Start,
+    /// The number of steps forward in one round of the cycle.
+    pub length: usize,
+    /// The obstacles the guard walks into during one round, in order.
+    pub obstacle_hits: Vec<ObstacleHit>,
+}
+
+pub struct GuardGallivant;
+
+impl Solution for GuardGallivant {
+    type Input = (LabMap, Start);
+    type PartOne = usize;
+    type PartTwo = u32;
+
+    const DAY: u8 = 6;
+    const TITLE: &'static str = "Guard Gallivant";
+    const LABELS: [&'static str; 2] = ["Tiles Covered", "Objects To Cause Loops"];
+    const INPUT_FILE: &'static str = "day_06_guard_gallivant.txt";
+
+    fn parse(data: &[u8]) -> Result<Self::Input> {
+        Ok(create_lab_map(data)?)
+    }
+
+    fn part_one((lab_map, start): &Self::Input) -> Result<usize> {
+        Ok(traverse_lab_part_one(lab_map, start).0)
+    }
+
+    fn part_two(input: &Self::Input) -> Result<u32> {
+        Self::part_two_with(input, false)
+    }
+
+    /// With `parallel` the obstacles are checked on multiple threads.
+    fn part_two_with((lab_map, start): &Self::Input, parallel: bool) -> Result<u32> {
+        let (_, first_visits) = traverse_lab_part_one(lab_map, start);
+        Ok(traverse_with_obstacles(lab_map, &first_visits, parallel).len() as u32)
+    }
+}
+
 pub fn solve_day_06(file: &str) -> Result<(usize, u32)> {
-    let data = fs::read(file)?;
-    let (mut lab_map, start) = create_lab_map(&data);
-    let (tiles_covered, visited_tiles) = traverse_lab_part_one(&lab_map, &start);
-    let obstacles_causing_loops = traverse_with_obstacles(&mut lab_map, &start, &visited_tiles);
-
-    Ok((tiles_covered, obstacles_causing_loops))
-}
-
-/// Create a map of the lab as HashMap and provide the start location (and direction).
-fn create_lab_map(data: &[u8]) -> (LabMap, Start) {
-    let mut lab_map: LabMap = HashMap::new();
-    let mut start: Option<(i32, i32)> = None;
-
-    let mut row: i32 = 0;
-    let mut col: i32 = 0;
-    data
-        .iter()
-        .for_each(
-            | char | {
-                match char {
-                    b'\n' => {row += 1; col = 0},
-                    b'#' => { lab_map.insert((row, col), Tile::Obstacle); col += 1; },
-                    b'.' => { lab_map.insert((row, col), Tile::Open); col += 1; },
-                    b'^' => {
-                        lab_map.insert((row, col), Tile::Open);
-                        start = Some((row, col));
-                        col += 1;
-                    },
-                    _ => unreachable!()
-                };
-            }
-        );
+    let data = read_input(file)?;
+    solve_from_bytes(&data)
+}
 
-    (lab_map, (start.unwrap(), (-1, 0)))  // We must have a start so just unwrap.
+pub fn solve_from_bytes(data: &[u8]) -> Result<(usize, u32)> {
+    GuardGallivant::solve_parts(data)
 }
 
-/// Take the map and place an obstacle on one of the visited tiles.
-/// Check if we get into an infinite loop and count.
-fn traverse_with_obstacles(
-    lab_map: &mut LabMap, start: &Start, visited_tiles: &[(i32, i32)]
-) -> u32 {
-    let mut obstacles_causing_loops: u32 = 0;
-    for tile in visited_tiles.iter() {
-        *lab_map.get_mut(tile).unwrap() = Tile::Obstacle;  // Create new obstacle.
+/// The positions where a new obstacle makes the guard walk in a loop, in reading order.
+pub fn find_loop_obstacles(data: &[u8]) -> Result<Vec<Position>> {
+    let (lab_map, start) = create_lab_map(data)?;
+    let (_, first_visits) = traverse_lab_part_one(&lab_map, &start);
 
-        match traverse_lab_part_two(lab_map, start) {
-            Route::Looping => obstacles_causing_loops += 1,
-            Route::Finite => {}  // Not relevant.
-        }
+    Ok(traverse_with_obstacles(&lab_map, &first_visits, false))
+}
 
-        *lab_map.get_mut(tile).unwrap() = Tile::Open;  // Undo the new obstacle.
+/// Follow the guard, optionally with a new obstacle placed, and return the cycle if the route loops.
+pub fn trace_patrol(data: &[u8], obstacle: Option<Position>) -> Result<Route> {
+    let (mut lab_map, start) = create_lab_map(data)?;
+    if let Some(position) = obstacle {
+        match lab_map.get_mut(position) {
+            Some(tile) if position != start.0 => *tile = Tile::Obstacle,
+            _ => bail!("an obstacle can not be placed at {:?}", position)
+        }
     }
 
+    Ok(traverse_lab_part_two(&lab_map, &start))
+}
+
+/// Create a grid of the lab and provide the start location (and direction).
+fn create_lab_map(data: &[u8]) -> Result<(LabMap, Start), ParseError> {
+    let mut grid = Grid::parse_with(
+        data, "'.', '#' or '^'", | char | matches!(char, b'.' | b'#' | b'^').then_some(char)
+    )?;
+    let start = grid.take_marker(b'^', b'.')?;
+
+    let lab_map = grid.map(
+        | &char | match char {
+            b'#' => Tile::Obstacle,
+            _ => Tile::Open
+        }
+    );
+
+    Ok((lab_map, (start, (-1, 0))))
+}
+
+/// Place an obstacle on each of the visited tiles and collect those that cause a loop, in reading order.
+///
+/// The route only changes once the guard first walks into the new obstacle,
+/// so start from where the guard was just before it first visited that tile.
+/// With `parallel` the tiles are checked on multiple threads.
+fn traverse_with_obstacles(lab_map: &LabMap, first_visits: &[(Position, Start)], parallel: bool) -> Vec<Position> {
+    let jump_table = create_jump_table(lab_map);
+    let causes_loop = | &(tile, before): &(Position, Start) | {
+        is_looping_with_obstacle(&jump_table, tile, &before).then_some(tile)
+    };
+
+    let mut obstacles_causing_loops: Vec<Position> = map_items(first_visits, parallel, causes_loop)
+        .into_iter()
+        .flatten()
+        .collect();
+
+    obstacles_causing_loops.sort_unstable();
     obstacles_causing_loops
 }
 
@@ -80,56 +143,131 @@ fn traverse_with_obstacles(
 ///
 /// Once we go out of bounds (coordinate not in map), count the visited tiles.
 ///
-/// For part two where we place obstacles provide an array of all visited tiles.
-/// Here we cannot place an obstacle on the start so remove that from the set.
-fn traverse_lab_part_one(lab_map: &LabMap, start: &Start) -> (usize, Vec<(i32, i32)>) {
-    let mut visited: HashSet<(i32, i32)> = HashSet::new();
+/// For part two where we place obstacles provide the visited tiles in the order of their first visit,
+/// together with the position and direction of the guard just before that visit.
+/// Here we cannot place an obstacle on the start so that one is never included.
+fn traverse_lab_part_one(lab_map: &LabMap, start: &Start) -> (usize, Vec<(Position, Start)>) {
+    let mut visited: HashSet<(i32, i32)> = HashSet::from([start.0]);
+    let mut first_visits: Vec<(Position, Start)> = Vec::new();
     let mut step = *start;
 
     loop {
         let (coord, direction) = step;
-        visited.insert(coord);
 
         let next_coord = (coord.0 + direction.0, coord.1 + direction.1);
-        match lab_map.get(&next_coord) {
+        match lab_map.get(next_coord) {
             Some(tile) => {
                 match *tile {
-                    Tile::Open => step = (next_coord, direction),
+                    Tile::Open => {
+                        if visited.insert(next_coord) {
+                            first_visits.push((next_coord, step));
+                        }
+                        step = (next_coord, direction)
+                    },
                     Tile::Obstacle => step = (coord, get_new_direction(&direction))
                 }
             }
             None => break
         }
     }
-    let visited_tiles_count = visited.len();
-    visited.remove(&start.0);  // For part two get the visited tiles minus the start.
 
-    (visited_tiles_count, Vec::from_iter(visited))
+    (visited.len(), first_visits)
+}
+
+/// Fill the jump table one direction at a time.
+///
+/// Walk through the tiles against the direction, so the tile in front is always done before the tile itself.
+fn create_jump_table(lab_map: &LabMap) -> JumpTable {
+    let mut jump_table: JumpTable = Grid::new(lab_map.rows(), lab_map.cols(), [None; 4]);
+    let mut positions: Vec<Position> = lab_map.positions().collect();
+
+    for (heading, &(dy, dx)) in DIRECTIONS_4.iter().enumerate() {
+        if dy + dx > 0 {  // South and east need the rows and columns from the end.
+            positions.reverse();
+        }
+
+        for &(row, col) in positions.iter() {
+            let next = (row + dy, col + dx);
+            jump_table[(row, col)][heading] = match lab_map.get(next) {
+                Some(Tile::Obstacle) => Some((row, col)),
+                Some(Tile::Open) => jump_table[next][heading],
+                None => None
+            };
+        }
+
+        if dy + dx > 0 {
+            positions.reverse();
+        }
+    }
+
+    jump_table
 }
 
+/// Jump from obstacle to obstacle with the jump table, with one extra obstacle placed.
+///
+/// The jump table does not know about the new obstacle, so check if it is in the way of each jump.
+/// If we stop at the same tile in the same direction as before we found an infinite looping route.
+/// A route only has a few stops, so searching a Vec is faster than hashing them.
+fn is_looping_with_obstacle(jump_table: &JumpTable, obstacle: Position, start: &Start) -> bool {
+    let mut visited: Vec<(Position, usize)> = Vec::new();
+    let mut position = start.0;
+    let mut heading = DIRECTIONS_4.iter().position(| &direction | direction == start.1).unwrap();
+
+    loop {
+        let (dy, dx) = DIRECTIONS_4[heading];
+        let distance = | (row, col): Position | (row - position.0) * dy + (col - position.1) * dx;
+        let in_line = (obstacle.0 - position.0) * dx == 0 && (obstacle.1 - position.1) * dy == 0;
+
+        let mut next = jump_table[position][heading];
+        if in_line && distance(obstacle) > 0 && next.is_none_or(| stop | distance(obstacle) <= distance(stop)) {
+            next = Some((obstacle.0 - dy, obstacle.1 - dx));
+        }
+
+        match next {
+            Some(stop) => position = stop,
+            None => return false
+        }
+
+        if visited.contains(&(position, heading)) {
+            return true
+        }
+        visited.push((position, heading));
+        heading = (heading + 1) % DIRECTIONS_4.len();
+    }
+}
 
 /// Traverse the lab keeping track of only the obstacles and directions we visited them.
 ///
 /// If we visit an obstacle in the same direction as before we found an infinite looping route.
+/// The cycle starts at that earlier visit, so store the hit number and steps taken at each visit.
 ///
 /// If we go out of bounds we found a finite route.
 fn traverse_lab_part_two(lab_map: &LabMap, start: &Start) -> Route {
-    let mut visited_obstacles: HashSet<((i32, i32), (i32, i32))> = HashSet::new();
+    let mut visited_obstacles: HashMap<Start, (usize, usize)> = HashMap::new();
+    let mut obstacle_hits: Vec<ObstacleHit> = Vec::new();
+    let mut steps_taken: usize = 0;
     let mut step = *start;
 
     loop {
         let(coord, direction) = step;
 
         let next_coord = (coord.0 + direction.0, coord.1 + direction.1);
-        match lab_map.get(&next_coord) {
+        match lab_map.get(next_coord) {
             Some(tile) => {
                 match *tile {
-                    Tile::Open => step = (next_coord, direction),
+                    Tile::Open => {
+                        step = (next_coord, direction);
+                        steps_taken += 1;
+                    },
                     Tile::Obstacle => {
-                        if !visited_obstacles.insert((coord, direction)) {
-                            return Route::Looping
+                        if let Some(&(hit, steps)) = visited_obstacles.get(&step) {
+                            return Route::Looping(
+                                Cycle { entry: step, length: steps_taken - steps, obstacle_hits: obstacle_hits.split_off(hit) }
+                            )
                         }
 
+                        visited_obstacles.insert(step, (obstacle_hits.len(), steps_taken));
+                        obstacle_hits.push((next_coord, direction));
                         step = (coord, get_new_direction(&direction))
                     }
                 }
@@ -156,6 +294,7 @@ fn get_new_direction(direction: &(i32, i32)) -> (i32, i32) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_random::Random;
 
     const EXAMPLE: &[u8] =
         b"....#.....\n\
@@ -171,15 +310,93 @@ mod tests {
 
     #[test]
     fn test_traverse_lab() {
-        let (lab_map, start) = create_lab_map(EXAMPLE);
-        let (tiles_covered, _) = traverse_lab_part_one(&lab_map, &start);
-        assert_eq!(tiles_covered, 41);
+        assert_eq!(solve_from_bytes(EXAMPLE).unwrap().0, 41);
     }
 
     #[test]
     fn test_lab_loops() {
-        let (mut lab_map, start) = create_lab_map(EXAMPLE);
-        let (_, visited_tiles) = traverse_lab_part_one(&lab_map, &start);
-        assert_eq!(traverse_with_obstacles(&mut lab_map, &start, &visited_tiles), 6);
+        assert_eq!(solve_from_bytes(EXAMPLE).unwrap().1, 6);
+    }
+
+    #[test]
+    fn test_loop_obstacles() {
+        assert_eq!(
+            find_loop_obstacles(EXAMPLE).unwrap(),
+            vec![(6, 3), (7, 6), (7, 7), (8, 1), (8, 3), (9, 7)]
+        );
+    }
+
+    #[test]
+    fn test_jump_table_matches_walking() {
+        // Compare with walking every candidate tile by tile on generated labs.
+        let mut random = Random::new(2024);
+        for _ in 0..20 {
+            let mut data: Vec<u8> = Vec::new();
+            for row in 0..30 {
+                for col in 0..30 {
+                    data.push(match (row, col) {
+                        (15, 15) => b'^',
+                        _ if random.below(100) < 6 => b'#',
+                        _ => b'.'
+                    });
+                }
+                data.push(b'\n');
+            }
+
+            let (mut lab_map, start) = create_lab_map(&data).unwrap();
+            if traverse_lab_part_two(&lab_map, &start) != Route::Finite {
+                continue  // The guard has to leave the lab without a new obstacle.
+            }
+
+            let mut expected: Vec<Position> = Vec::new();
+            for position in lab_map.positions().filter(| &position | position != start.0).collect::<Vec<_>>() {
+                if lab_map[position] == Tile::Obstacle {
+                    continue
+                }
+
+                lab_map[position] = Tile::Obstacle;
+                if let Route::Looping(_) = traverse_lab_part_two(&lab_map, &start) {
+                    expected.push(position);
+                }
+                lab_map[position] = Tile::Open;
+            }
+
+            assert_eq!(find_loop_obstacles(&data).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_trace_patrol() {
+        assert_eq!(trace_patrol(EXAMPLE, None).unwrap(), Route::Finite);
+
+        // The first example loop from the puzzle, an obstacle next to the starting position.
+        let route = trace_patrol(EXAMPLE, Some((6, 3))).unwrap();
+        assert_eq!(
+            route,
+            Route::Looping(
+                Cycle {
+                    entry: ((1, 4), (-1, 0)),
+                    length: 18,
+                    obstacle_hits: vec![((0, 4), (-1, 0)), ((1, 9), (0, 1)), ((7, 8), (1, 0)), ((6, 3), (0, -1))]
+                }
+            )
+        );
+
+        assert!(trace_patrol(EXAMPLE, Some((6, 4))).is_err());
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let data = String::from_utf8(EXAMPLE.to_vec()).unwrap().replace('\n', "\r\n");
+        assert_eq!(solve_from_bytes(data.as_bytes()).unwrap(), (41, 6));
+    }
+
+    #[test]
+    fn test_create_lab_map_errors() {
+        let error = create_lab_map(b"....#\r\n..^.x\r\n").err().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 5, "x"));
+
+        let error = create_lab_map(b"....#\n.....\n").err().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (3, 1, ""));
     }
 }
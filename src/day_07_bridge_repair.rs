@@ -1,31 +1,92 @@
-use std::fs;
 use std::ops::{Add, Mul};
 
 use anyhow::Result as Result;
+use crate::input::read_input_to_string;
+use crate::map_items;
+use crate::parse_error::{parse_field, ParseError};
+use crate::solution::Solution;
 
-pub fn solve_day_07(file: &str) -> Result<(u64, u64)> {
-    let data = fs::read_to_string(file)?;
-
-    let mut total_of_valid_equations = 0;
-    let mut total_valid_with_concat = 0;
-
-    for line in data.lines() {
-        let (target, series) = line.split_once(": ").unwrap();
-        let target: u64 = target.parse().unwrap();
-        let series: Vec<u64> = series
-            .split_ascii_whitespace()
-            .map(| val | val.parse().unwrap())
-            .collect();
-
-        if let Some(value) = check_valid_equation(&series, target, false) {
-            total_of_valid_equations += value;
-            total_valid_with_concat += value;
-        } else if let Some(value) = check_valid_equation(&series, target, true) {
-            total_valid_with_concat += value;
+type Equations = Vec<(u64, Vec<u64>)>;
+
+pub struct BridgeRepair;
+
+impl Solution for BridgeRepair {
+    type Input = Equations;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    const DAY: u8 = 7;
+    const TITLE: &'static str = "Bridge Repair";
+    const LABELS: [&'static str; 2] = ["Total Calibration Result", "Total Calibration Result"];
+    const INPUT_FILE: &'static str = "day_07_bridge_repair.txt";
+
+    fn parse(data: &[u8]) -> Result<Equations> {
+        let mut equations: Equations = Vec::new();
+
+        for (i, line) in std::str::from_utf8(data)?.lines().enumerate() {
+            let Some((target, series)) = line.split_once(": ") else {
+                return Err(ParseError::in_line(i, line, line, "an equation like '190: 10 19'").into());
+            };
+
+            let target: u64 = parse_field(i, line, target, "a test value")?;
+            let series: Vec<u64> = series
+                .split_ascii_whitespace()
+                .map(| val | parse_field(i, line, val, "a number"))
+                .collect::<Result<_, _>>()?;
+
+            if series.is_empty() {
+                return Err(ParseError::new(i + 1, line.len() + 1, "", "a number").into());
+            }
+
+            equations.push((target, series));
         }
+
+        Ok(equations)
+    }
+
+    fn part_one(equations: &Equations) -> Result<u64> {
+        Self::part_one_with(equations, false)
+    }
+
+    fn part_two(equations: &Equations) -> Result<u64> {
+        Self::part_two_with(equations, false)
+    }
+
+    /// With `parallel` the equations are checked on multiple threads.
+    fn part_one_with(equations: &Equations, parallel: bool) -> Result<u64> {
+        Ok(
+            map_items(equations, parallel, | (target, series) | check_valid_equation(series, *target, false))
+                .into_iter()
+                .flatten()
+                .sum()
+        )
     }
 
-    Ok((total_of_valid_equations, total_valid_with_concat))
+    /// The equations without concatenation are checked first, because that is much faster.
+    fn part_two_with(equations: &Equations, parallel: bool) -> Result<u64> {
+        Ok(
+            map_items(
+                equations,
+                parallel,
+                | (target, series) | {
+                    check_valid_equation(series, *target, false)
+                        .or_else(|| check_valid_equation(series, *target, true))
+                }
+            )
+                .into_iter()
+                .flatten()
+                .sum()
+        )
+    }
+}
+
+pub fn solve_day_07(file: &str) -> Result<(u64, u64)> {
+    let data = read_input_to_string(file)?;
+    solve_from_str(&data)
+}
+
+pub fn solve_from_str(data: &str) -> Result<(u64, u64)> {
+    BridgeRepair::solve_parts(data.as_bytes())
 }
 
 
@@ -112,4 +173,15 @@ mod tests {
         assert_eq!(check_valid_equation(&EXAMPLE7, 192, true), Some(192));
         assert_eq!(check_valid_equation(&EXAMPLE8, 21037, true), None);
     }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = BridgeRepair::parse(b"190: 10 19\n3267 81 40 27\n").err().unwrap();
+        let error = error.downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 1, "3267 81 40 27"));
+
+        let error = BridgeRepair::parse(b"190: 10 19\n3267: 81 4O 27\n").err().unwrap();
+        let error = error.downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 10, "4O"));
+    }
 }
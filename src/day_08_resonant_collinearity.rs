@@ -1,55 +1,66 @@
 use std::collections::{HashMap, HashSet};
-use std::fs;
 
 use itertools::Itertools;
 use anyhow::Result as Result;
 
+use crate::grid::Grid;
+use crate::input::read_input;
+use crate::parse_error::ParseError;
+use crate::solution::Solution;
+
 type NodeMap = HashMap<u8, Coordinates>;
 type Coordinates = Vec<(i32, i32)>;
 type Antinodes = HashSet<(i32, i32)>;
 
-pub fn solve_day_08(file: &str) -> Result<(usize, usize)> {
-    let data = fs::read(file)?;
+pub struct ResonantCollinearity;
+
+impl Solution for ResonantCollinearity {
+    type Input = (NodeMap, Grid<u8>);
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    const DAY: u8 = 8;
+    const TITLE: &'static str = "Resonant Collinearity";
+    const LABELS: [&'static str; 2] = ["# Antinodes", "# Antinodes"];
+    const INPUT_FILE: &'static str = "day_08_resonant_collinearity.txt";
+
+    fn parse(data: &[u8]) -> Result<Self::Input> {
+        Ok(generate_node_sets(data)?)
+    }
 
-    let (node_map, max_row, max_col) = generate_node_sets(&data);
-    let antinode_count_one = count_antinodes_without_resonance(&node_map, max_row, max_col);
-    let antinode_count_two =  count_antinodes_with_resonance(&node_map, max_row, max_col);
+    fn part_one((node_map, map): &Self::Input) -> Result<usize> {
+        Ok(count_antinodes_without_resonance(node_map, map))
+    }
 
-    Ok((antinode_count_one, antinode_count_two))
+    fn part_two((node_map, map): &Self::Input) -> Result<usize> {
+        Ok(count_antinodes_with_resonance(node_map, map))
+    }
 }
 
-/// Create a map where all nodes of the same key are grouped.
-fn generate_node_sets(map: &[u8]) -> (NodeMap, i32, i32) {
-    let mut node_map: NodeMap = HashMap::new();
+pub fn solve_day_08(file: &str) -> Result<(usize, usize)> {
+    let data = read_input(file)?;
+    solve_from_bytes(&data)
+}
 
-    let mut row = 0;
-    let mut col = 0;
+pub fn solve_from_bytes(data: &[u8]) -> Result<(usize, usize)> {
+    ResonantCollinearity::solve_parts(data)
+}
 
-    let mut max_row = 0;
-    let mut max_col = 0;
+/// Create a map where all nodes of the same key are grouped.
+fn generate_node_sets(data: &[u8]) -> Result<(NodeMap, Grid<u8>), ParseError> {
+    let map = Grid::parse_with(
+        data, "'.' or an antenna", | char | (char == b'.' || char.is_ascii_alphanumeric()).then_some(char)
+    )?;
 
-    map
-        .iter()
-        .for_each(
-            | char | {
-                match char {
-                    b'.' => col += 1,
-                    b'\n' => { row += 1; col = 0; }
-                    _ => {
-                        node_map
-                            .entry(*char)
-                            .and_modify(| val | val.push((row, col)))
-                            .or_insert(vec![(row, col)]);
-                        col += 1;
-                    }
-                }
-                max_row = max_row.max(row);
-                max_col = max_col.max(col);
-            }
-        );
+    let mut node_map: NodeMap = HashMap::new();
+    for (coord, &char) in map.iter().filter(| (_, &char) | char != b'.') {
+        node_map
+            .entry(char)
+            .and_modify(| val | val.push(coord))
+            .or_insert(vec![coord]);
+    }
 
-    // Remove 1 from maxes, last row is newline and each row ends in newline.
-    (node_map, max_row - 1, max_col - 1)
+    Ok((node_map, map))
 }
 
 /// Count the antinodes without resonance.
@@ -58,7 +69,7 @@ fn generate_node_sets(map: &[u8]) -> (NodeMap, i32, i32) {
 /// Because those are technically "resonant" nodes.
 /// 
 /// Finally, do a bounds check on the nodes.
-fn count_antinodes_without_resonance(node_map: &NodeMap, max_row: i32, max_col: i32) -> usize {
+fn count_antinodes_without_resonance(node_map: &NodeMap, map: &Grid<u8>) -> usize {
     let mut antinodes: Antinodes = HashSet::new();
 
     for (_key, coordinates) in node_map.iter() {
@@ -78,11 +89,7 @@ fn count_antinodes_without_resonance(node_map: &NodeMap, max_row: i32, max_col:
     // Remove out of bounds entries.
     antinodes
         .iter()
-        .filter(
-            | (row, col ) | {
-                *row >= 0 && *row <= max_row && *col >= 0 && *col <= max_col
-            }
-        )
+        .filter(| coord | map.contains(**coord))
         .count()
 }
 
@@ -90,7 +97,7 @@ fn count_antinodes_without_resonance(node_map: &NodeMap, max_row: i32, max_col:
 /// 
 /// To get the resonance we hardcoded just 50 steps in each direction.
 /// This is very ugly, but it worked and was fast enough.
-fn count_antinodes_with_resonance(node_map: &NodeMap, max_row: i32, max_col: i32) -> usize {
+fn count_antinodes_with_resonance(node_map: &NodeMap, map: &Grid<u8>) -> usize {
     let mut antinodes: Antinodes = HashSet::new();
 
     for (_key, val) in node_map.iter() {
@@ -104,11 +111,7 @@ fn count_antinodes_with_resonance(node_map: &NodeMap, max_row: i32, max_col: i32
     // Remove out of bounds entries.
     antinodes
         .iter()
-        .filter(
-            | (row, col ) | {
-                *row >= 0 && *row <= max_row && *col >= 0 && *col <= max_col
-            }
-        )
+        .filter(| coord | map.contains(**coord))
         .count()
 }
 
@@ -184,13 +187,11 @@ mod tests {
 
     #[test]
     fn test_count_antinodes_without_resonance() {
-        let (node_map, max_row, max_col) = generate_node_sets(EXAMPLE);
-        assert_eq!(count_antinodes_without_resonance(&node_map, max_row, max_col), 14);
+        assert_eq!(solve_from_bytes(EXAMPLE).unwrap().0, 14);
     }
 
     #[test]
     fn test_count_antinodes_with_resonance() {
-        let (node_map, max_row, max_col) = generate_node_sets(EXAMPLE);
-        assert_eq!(count_antinodes_with_resonance(&node_map, max_row, max_col), 34);
+        assert_eq!(solve_from_bytes(EXAMPLE).unwrap().1, 34);
     }
 }
@@ -1,8 +1,13 @@
-use std::fs;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 
 use anyhow::Result as Result;
 use itertools::Itertools;
 
+use crate::input::read_input;
+use crate::parse_error::ParseError;
+use crate::solution::Solution;
+
 type Fragments = Vec<Option<usize>>;
 
 #[derive(Copy, Clone, Debug)]
@@ -17,33 +22,58 @@ impl DataFragment {
     fn new(size: u8, id: Option<usize>) -> Self {
         Self { size, id }
     }
-
-    #[inline]
-    fn is_none(&self) -> bool {
-        self.id.is_none()
-    }
-
-    #[inline]
-    fn is_some(&self) -> bool {
-        self.id.is_some()
-    }
 }
 
 
-pub fn solve_day_09(file: &str) -> Result<(usize, u32)> {
-    let data: Vec<u8> = fs::read(file)?;
+pub struct DiskFragmenter;
+
+impl Solution for DiskFragmenter {
+    type Input = Vec<u8>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    const DAY: u8 = 9;
+    const TITLE: &'static str = "Disk Fragmenter";
+    const LABELS: [&'static str; 2] = ["Checksum", "Checksum"];
+    const INPUT_FILE: &'static str = "day_09_disk_fragmenter.txt";
+
+    /// Convert the disk map to the numeric sizes, without the trailing newline.
+    fn parse(data: &[u8]) -> Result<Vec<u8>> {
+        Ok(
+            data
+                .trim_ascii_end()
+                .iter()
+                .enumerate()
+                .map(
+                    | (i, byte) | match byte {
+                        b'0'..=b'9' => Ok(byte - b'0'),
+                        _ => Err(ParseError::at_byte(data, i, "a digit"))
+                    }
+                )
+                .collect::<Result<_, _>>()?
+        )
+    }
 
-    let data: Vec<u8> = data[0..data.len()-1]
-        .iter()
-        .map(| byte | byte - b'0')
-        .collect();
+    fn part_one(data: &Vec<u8>) -> Result<usize> {
+        let fragments = create_single_fragments(data);
+        let defragged_data = defragment_single_blocks(&fragments);
+        Ok(calculate_checksum(&defragged_data))
+    }
 
+    fn part_two(data: &Vec<u8>) -> Result<usize> {
+        let fragments = create_fragment_blocks(data);
+        let defragged_data = defragment_complete_blocks(&fragments);
+        Ok(calculate_checksum(&defragged_data))
+    }
+}
 
-    let fragments = create_single_fragments(&data);
-    let defragged_data = defragment_single_blocks(&fragments);
-    let checksum = calculate_checksum(&defragged_data);
+pub fn solve_day_09(file: &str) -> Result<(usize, usize)> {
+    let data = read_input(file)?;
+    solve_from_bytes(&data)
+}
 
-    Ok((checksum, 0))
+pub fn solve_from_bytes(data: &[u8]) -> Result<(usize, usize)> {
+    DiskFragmenter::solve_parts(data)
 }
 
 fn create_single_fragments(data: &[u8]) -> Fragments {
@@ -68,7 +98,8 @@ fn create_single_fragments(data: &[u8]) -> Fragments {
     fragments
 }
 
-fn defragment_single_blocks(fragments: &Fragments) -> Vec<usize> {
+/// Move single blocks from the end of the disk to the first empty block.
+fn defragment_single_blocks(fragments: &Fragments) -> Fragments {
     let mut new_fragments = fragments.clone();
     let mut last_idx: usize = fragments.len();
 
@@ -87,9 +118,6 @@ fn defragment_single_blocks(fragments: &Fragments) -> Vec<usize> {
     }
 
     new_fragments
-        .into_iter()
-        .flatten()
-        .collect()
 }
 
 fn create_fragment_blocks(data: &[u8]) -> Vec<DataFragment> {
@@ -112,39 +140,53 @@ fn create_fragment_blocks(data: &[u8]) -> Vec<DataFragment> {
     fragments
 }
 
-fn defragment_complete_blocks(fragments: Vec<DataFragment>) -> Vec<DataFragment> {
-    let mut new_fragments= fragments.clone();
-    let mut insert_indices: Vec<usize> = Vec::new();
-
-    for filled_idx_raw in fragments.iter().positions(| val | val.is_some()).rev() {
-        let filled_idx = filled_idx_raw + insert_indices.iter().filter(| val | **val > filled_idx_raw).count();
+/// Move complete files, in descending id order, to the leftmost empty span that fits.
+///
+/// Each file is moved at most once and only to the left.
+/// The empty spans are kept in a min-heap of start positions for each span size (0-9),
+/// so the leftmost fitting span is the smallest start of the heaps with a size >= the file size.
+/// The space left by a moved file is never used again, because all lower ids are to the left of it.
+fn defragment_complete_blocks(fragments: &[DataFragment]) -> Fragments {
+    let mut files: Vec<(usize, u8, usize)> = Vec::new();  // (start, size, id)
+    let mut empty_spans: [BinaryHeap<Reverse<usize>>; 10] = Default::default();
+
+    let mut position: usize = 0;
+    for fragment in fragments.iter() {
+        match fragment.id {
+            Some(id) => files.push((position, fragment.size, id)),
+            None => empty_spans[fragment.size as usize].push(Reverse(position))
+        }
+        position += fragment.size as usize;
+    }
 
-        for empty_idx in new_fragments.iter().positions(| val | val.is_none()) {
-            if new_fragments[empty_idx].size >= new_fragments[filled_idx].size {
+    for (start, size, _) in files.iter_mut().rev() {
+        let leftmost_span = (*size as usize..empty_spans.len())
+            .filter_map(| span_size | empty_spans[span_size].peek().map(| span_start | (span_start.0, span_size)))
+            .filter(| (span_start, _) | span_start < start)
+            .min();
 
-                new_fragments.insert(empty_idx, new_fragments[filled_idx]);
-                new_fragments[empty_idx + 1].size -= new_fragments[empty_idx].size;
-                new_fragments.remove(filled_idx + 1);
-                insert_indices.push(empty_idx);
-                break
-            }
+        if let Some((span_start, span_size)) = leftmost_span {
+            empty_spans[span_size].pop();
+            empty_spans[span_size - *size as usize].push(Reverse(span_start + *size as usize));
+            *start = span_start;
         }
     }
 
-    // FIXME Blocks are in the right order,
-    //   but there are empty blocks missing in the middle so the checksum will not be correct.
+    let mut new_fragments: Fragments = vec![None; position];
+    for (start, size, id) in files {
+        new_fragments[start..start + size as usize].fill(Some(id));
+    }
+
     new_fragments
 }
 
-fn calculate_checksum(data: &[usize]) -> usize {
-    let mut checksum: usize = 0;
-
+/// Sum of the position times the file id, the empty blocks are skipped.
+fn calculate_checksum(data: &[Option<usize>]) -> usize {
     data
         .iter()
         .enumerate()
-        .for_each(| (i, &val) | checksum += i * val);
-
-    checksum
+        .filter_map(| (i, val) | val.map(| id | i * id))
+        .sum()
 }
 
 
@@ -156,17 +198,32 @@ mod tests {
 
     #[test]
     fn test_defragment_single_blocks() {
-        let data: Vec<u8> = EXAMPLE.iter().map(| val | val - b'0').collect();
-        let fragments = create_single_fragments(&data);
-        let defragged = defragment_single_blocks(&fragments);
-        assert_eq!(calculate_checksum(&defragged), 1928);
+        assert_eq!(solve_from_bytes(EXAMPLE).unwrap().0, 1928);
     }
 
     #[test]
     fn test_defragment_complete_blocks() {
+        assert_eq!(solve_from_bytes(EXAMPLE).unwrap().1, 2858);
+    }
+
+    #[test]
+    fn test_defragment_complete_blocks_layout() {
         let data: Vec<u8> = EXAMPLE.iter().map(| val | val - b'0').collect();
-        let fragments = create_fragment_blocks(&data);
-        let defragged = defragment_complete_blocks(fragments);
-        println!("{:?}", defragged);
+        let defragged = defragment_complete_blocks(&create_fragment_blocks(&data));
+        let layout: String = defragged
+            .iter()
+            .map(| val | val.map_or('.', | id | char::from_digit(id as u32, 10).unwrap()))
+            .collect();
+
+        assert_eq!(layout, "00992111777.44.333....5555.6666.....8888..");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(DiskFragmenter::parse(b"2333\r\n").unwrap(), vec![2, 3, 3, 3]);
+
+        let error = DiskFragmenter::parse(b"23x3\n").err().unwrap();
+        assert_eq!(error.downcast::<ParseError>().unwrap(), ParseError::new(1, 3, "x", "a digit"));
+        assert!(DiskFragmenter::parse(b"23/3").is_err());
     }
 }
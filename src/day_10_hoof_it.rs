@@ -1,71 +1,71 @@
 use std::collections::HashSet;
-use std::fs;
 
 use anyhow::Result as Result;
 
-type TopoGraphicMap = Vec<Vec<u8>>;
+use crate::grid::Grid;
+use crate::input::read_input;
+use crate::parse_error::ParseError;
+use crate::solution::Solution;
+
+type TopoGraphicMap = Grid<u8>;
 type Coordinate = (i32, i32, u8);
 
-const DIRECTIONS: [(i32, i32); 4] = [
-    (-1, 0),  // North
-    (0, 1),   //  East
-    (1, 0),   // South
-    (0, -1)   // West
-];
+pub struct HoofIt;
 
-pub fn solve_day_10(file: &str) -> Result<(u32, u32)> {
-    let data = fs::read(file)?;
-
-    let mut trail_score: u32 = 0;
-    let mut trail_rating: u32 = 0;
-
-    let (topographic_map, starts) = create_topographic_map(&data);
-    starts
-        .iter()
-        .for_each(
-            | start | {
-                trail_score += hike_trail(&topographic_map, start, true);
-                trail_rating += hike_trail(&topographic_map, start, false);
-            }
-        );
+impl Solution for HoofIt {
+    type Input = (TopoGraphicMap, Vec<Coordinate>);
+    type PartOne = u32;
+    type PartTwo = u32;
+
+    const DAY: u8 = 10;
+    const TITLE: &'static str = "Hoof It";
+    const LABELS: [&'static str; 2] = ["Hiking Trails Score", "Hiking Trails Rating"];
+    const INPUT_FILE: &'static str = "day_10_hoof_it.txt";
+
+    fn parse(data: &[u8]) -> Result<Self::Input> {
+        Ok(create_topographic_map(data)?)
+    }
+
+    fn part_one((topographic_map, starts): &Self::Input) -> Result<u32> {
+        Ok(
+            starts
+                .iter()
+                .map(| start | hike_trail(topographic_map, start, true))
+                .sum()
+        )
+    }
 
+    fn part_two((topographic_map, starts): &Self::Input) -> Result<u32> {
+        Ok(
+            starts
+                .iter()
+                .map(| start | hike_trail(topographic_map, start, false))
+                .sum()
+        )
+    }
+}
+
+pub fn solve_day_10(file: &str) -> Result<(u32, u32)> {
+    let data = read_input(file)?;
+    solve_from_bytes(&data)
+}
 
-    Ok((trail_score, trail_rating))
+pub fn solve_from_bytes(data: &[u8]) -> Result<(u32, u32)> {
+    HoofIt::solve_parts(data)
 }
 
 /// Create a map of the hiking trail elevation, also track the starting positions (height=0).
-fn create_topographic_map(data: &[u8]) -> (TopoGraphicMap, Vec<Coordinate>) {
-    let mut topographic_map: TopoGraphicMap = Vec::new();
-    let mut row: Vec<u8> = Vec::new();
-
-    let mut starts: Vec<Coordinate> = Vec::new();
-    let mut x_val = 0;
-    let mut y_val = 0;
-
-    data
-        .iter()
-        .for_each(
-            | char | {
-                match char {
-                    b'\n' => {
-                        topographic_map.push(row.clone());
-                        row = Vec::new();
-                        x_val = 0; y_val += 1;
-                    },
-                    b'0' => {
-                        row.push(char - b'0');
-                        starts.push((y_val, x_val, char - b'0'));
-                        x_val += 1
-                    }
-                    _ => {
-                        row.push(char - b'0');
-                        x_val += 1;
-                    }
-                }
-            }
-        );
+fn create_topographic_map(data: &[u8]) -> Result<(TopoGraphicMap, Vec<Coordinate>), ParseError> {
+    let topographic_map = Grid::parse_with(
+        data, "a height from '0' to '9'", | char | char.is_ascii_digit().then(|| char - b'0')
+    )?;
 
-    (topographic_map, starts)
+    let starts: Vec<Coordinate> = topographic_map
+        .find_all(| &height | height == 0)
+        .map(| (row, col) | (row, col, 0))
+        .collect();
+
+    Ok((topographic_map, starts))
 }
 
 /// Hike the trail by breadth-first-search, until we reach a summit (height=9).
@@ -91,16 +91,10 @@ fn hike_trail(
             continue
         }
 
-        for direction in DIRECTIONS {
-            let new_row = row + direction.0;
-            let new_col = col + direction.1;
-
-            if let Some(slice) = topographic_map.get(new_row as usize) {
-                if let Some(&new_val) = slice.get(new_col as usize) {
-                    if new_val == val + 1 {
-                        queue.push((new_row, new_col, new_val));
-                    }
-                }
+        for (new_row, new_col) in topographic_map.neighbors((row, col)) {
+            let new_val = topographic_map[(new_row, new_col)];
+            if new_val == val + 1 {
+                queue.push((new_row, new_col, new_val));
             }
         }
     }
@@ -125,33 +119,11 @@ mod tests {
 
     #[test]
     fn test_part_one() {
-        let (map, starts) = create_topographic_map(EXAMPLE);
-        let mut goals_reached = 0;
-
-        starts
-            .iter()
-            .for_each(
-                | start | {
-                    goals_reached += hike_trail(&map, start, true);
-                }
-            );
-
-        assert_eq!(goals_reached, 36);
+        assert_eq!(solve_from_bytes(EXAMPLE).unwrap().0, 36);
     }
 
     #[test]
     fn test_part_two() {
-        let (map, starts) = create_topographic_map(EXAMPLE);
-        let mut goals_reached = 0;
-
-        starts
-            .iter()
-            .for_each(
-                | start | {
-                    goals_reached += hike_trail(&map, start, false);
-                }
-            );
-
-        assert_eq!(goals_reached, 81);
+        assert_eq!(solve_from_bytes(EXAMPLE).unwrap().1, 81);
     }
 }
@@ -1,27 +1,64 @@
 use std::collections::HashMap;
-use std::fs;
 use anyhow::Result as Result;
 
+use crate::input::read_input_to_string;
+use crate::parse_error::parse_field;
+use crate::solution::Solution;
+
 type Memory = HashMap<(u64, u8), u64>;
 
+pub struct PlutonianPebbles;
+
+impl Solution for PlutonianPebbles {
+    type Input = Vec<u64>;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    const DAY: u8 = 11;
+    const TITLE: &'static str = "Plutonian Pebbles";
+    const LABELS: [&'static str; 2] = ["Total Pebbles", "Total Pebbles"];
+    const INPUT_FILE: &'static str = "day_11_plutonian_pebbles.txt";
+
+    fn parse(data: &[u8]) -> Result<Vec<u64>> {
+        let mut stones: Vec<u64> = Vec::new();
+
+        for (i, line) in std::str::from_utf8(data)?.lines().enumerate() {
+            for val in line.split_ascii_whitespace() {
+                stones.push(parse_field(i, line, val, "a number")?);
+            }
+        }
+
+        Ok(stones)
+    }
+
+    fn part_one(stones: &Vec<u64>) -> Result<u64> {
+        let mut memory: Memory = HashMap::new();
+        Ok(
+            stones
+                .iter()
+                .map(| val | counting_stones(*val, 25, &mut memory))
+                .sum()
+        )
+    }
+
+    fn part_two(stones: &Vec<u64>) -> Result<u64> {
+        let mut memory: Memory = HashMap::new();
+        Ok(
+            stones
+                .iter()
+                .map(| val | counting_stones(*val, 75, &mut memory))
+                .sum()
+        )
+    }
+}
+
 pub fn solve_day_11(file: &str) -> Result<(u64, u64)> {
-    let data: Vec<u64> = fs::read_to_string(file)?
-        .split_ascii_whitespace()
-        .map(| val | val.parse().unwrap())
-        .collect();
-    
-    let mut memory: Memory = HashMap::new();
-    let stones_part_one: u64 = data
-        .iter()
-        .map(| val | counting_stones(*val, 25, &mut memory))
-        .sum();
-    
-    let stones_part_two: u64 = data
-        .into_iter()
-        .map(| val | counting_stones(val, 75, &mut memory))
-        .sum();
-    
-    Ok((stones_part_one, stones_part_two))
+    let data = read_input_to_string(file)?;
+    solve_from_str(&data)
+}
+
+pub fn solve_from_str(data: &str) -> Result<(u64, u64)> {
+    PlutonianPebbles::solve_parts(data.as_bytes())
 }
 
 /// Count the stones using a recursive function with memoization.
@@ -35,16 +72,16 @@ fn counting_stones(stone: u64, blinks: u8, memory: &mut Memory) -> u64 {
     }
 
     // Do conversion and recursion.
-    let mut count = 0;  // TODO clippy complains, check how to best improve.
-    if stone == 0 {
-        count = counting_stones(1, blinks - 1, memory);
-    } else if (stone.ilog10() + 1) % 2 == 0 {
-        let m = 10u64.pow((stone.ilog10() + 1) / 2);
-        count = counting_stones(stone / m, blinks - 1, memory)
-            + counting_stones(stone % m, blinks - 1, memory);
+    let digits = stone.checked_ilog10().unwrap_or(0) + 1;
+    let count = if stone == 0 {
+        counting_stones(1, blinks - 1, memory)
+    } else if digits.is_multiple_of(2) {
+        let m = 10u64.pow(digits / 2);
+        counting_stones(stone / m, blinks - 1, memory)
+            + counting_stones(stone % m, blinks - 1, memory)
     } else {
-        count = counting_stones(stone * 2024, blinks - 1, memory);
-    }
+        counting_stones(stone * 2024, blinks - 1, memory)
+    };
     
     memory.insert((stone, blinks), count);  // Add to memory
     count
@@ -1,5 +1,205 @@
 use anyhow::Result as Result;
 
-pub fn solve_day_12(_file: &str)  -> Result<(u8, u8)> {
-    Ok((0, 0))
-}
\ No newline at end of file
+use crate::grid::{Grid, Position, DIRECTIONS_4};
+use crate::input::read_input;
+use crate::solution::Solution;
+
+type GardenMap = Grid<u8>;
+type Region = Vec<Position>;
+
+pub struct GardenGroups;
+
+impl Solution for GardenGroups {
+    type Input = GardenMap;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    const DAY: u8 = 12;
+    const TITLE: &'static str = "Garden Groups";
+    const LABELS: [&'static str; 2] = ["Fence Price", "Bulk Discount Price"];
+    const INPUT_FILE: &'static str = "day_12_garden_groups.txt";
+
+    fn parse(data: &[u8]) -> Result<GardenMap> {
+        Ok(
+            Grid::parse_with(
+                data, "a plant from 'A' to 'Z'", | char | char.is_ascii_uppercase().then_some(char)
+            )?
+        )
+    }
+
+    fn part_one(garden: &GardenMap) -> Result<usize> {
+        Ok(
+            find_regions(garden)
+                .iter()
+                .map(| region | region.len() * count_perimeter(garden, region))
+                .sum()
+        )
+    }
+
+    fn part_two(garden: &GardenMap) -> Result<usize> {
+        Ok(
+            find_regions(garden)
+                .iter()
+                .map(| region | region.len() * count_sides(garden, region))
+                .sum()
+        )
+    }
+}
+
+pub fn solve_day_12(file: &str) -> Result<(usize, usize)> {
+    let data = read_input(file)?;
+    solve_from_bytes(&data)
+}
+
+pub fn solve_from_bytes(data: &[u8]) -> Result<(usize, usize)> {
+    GardenGroups::solve_parts(data)
+}
+
+/// Find the regions of the same plant by flood filling from every plot that is not in a region yet.
+fn find_regions(garden: &GardenMap) -> Vec<Region> {
+    let mut visited: Grid<bool> = Grid::new(garden.rows(), garden.cols(), false);
+    let mut regions: Vec<Region> = Vec::new();
+
+    for start in garden.positions() {
+        if visited[start] {
+            continue
+        }
+
+        let plant = garden[start];
+        let mut region: Region = Vec::new();
+        let mut queue: Vec<Position> = vec![start];
+        visited[start] = true;
+
+        while let Some(current) = queue.pop() {
+            region.push(current);
+
+            for neighbor in garden.neighbors(current) {
+                if garden[neighbor] == plant && !visited[neighbor] {
+                    visited[neighbor] = true;
+                    queue.push(neighbor);
+                }
+            }
+        }
+
+        regions.push(region);
+    }
+
+    regions
+}
+
+/// Check if the position is a plot of the same plant, positions outside the garden never are.
+#[inline]
+fn is_same_plant(garden: &GardenMap, position: Position, plant: u8) -> bool {
+    garden.get(position) == Some(&plant)
+}
+
+/// Each side of a plot that does not border the same plant needs a fence.
+fn count_perimeter(garden: &GardenMap, region: &Region) -> usize {
+    let plant = garden[region[0]];
+
+    region
+        .iter()
+        .map(
+            | (row, col) | {
+                DIRECTIONS_4
+                    .iter()
+                    .filter(| (dy, dx) | !is_same_plant(garden, (row + dy, col + dx), plant))
+                    .count()
+            }
+        )
+        .sum()
+}
+
+/// The number of sides of a region is equal to the number of corners.
+///
+/// For each plot check the 4 pairs of adjacent directions (e.g. north and east).
+/// An outer corner has neither neighbor in the region.
+/// An inner corner has both neighbors in the region, but not the diagonal between them.
+fn count_sides(garden: &GardenMap, region: &Region) -> usize {
+    let plant = garden[region[0]];
+
+    region
+        .iter()
+        .map(
+            | (row, col) | {
+                (0..DIRECTIONS_4.len())
+                    .filter(
+                        | i | {
+                            let (dy_one, dx_one) = DIRECTIONS_4[*i];
+                            let (dy_two, dx_two) = DIRECTIONS_4[(i + 1) % DIRECTIONS_4.len()];
+
+                            let one = is_same_plant(garden, (row + dy_one, col + dx_one), plant);
+                            let two = is_same_plant(garden, (row + dy_two, col + dx_two), plant);
+                            let diagonal = is_same_plant(
+                                garden, (row + dy_one + dy_two, col + dx_one + dx_two), plant
+                            );
+
+                            (!one && !two) || (one && two && !diagonal)
+                        }
+                    )
+                    .count()
+            }
+        )
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE1: &[u8] =
+        b"AAAA\n\
+        BBCD\n\
+        BBCC\n\
+        EEEC\n";
+
+    const EXAMPLE2: &[u8] =
+        b"OOOOO\n\
+        OXOXO\n\
+        OOOOO\n\
+        OXOXO\n\
+        OOOOO\n";
+
+    const EXAMPLE3: &[u8] =
+        b"RRRRIICCFF\n\
+        RRRRIICCCF\n\
+        VVRRRCCFFF\n\
+        VVRCCCJFFF\n\
+        VVVVCJJCFE\n\
+        VVIVCCJJEE\n\
+        VVIIICJJEE\n\
+        MIIIIIJJEE\n\
+        MIIISIJEEE\n\
+        MMMISSJEEE\n";
+
+    const EXAMPLE4: &[u8] =
+        b"EEEEE\n\
+        EXXXX\n\
+        EEEEE\n\
+        EXXXX\n\
+        EEEEE\n";
+
+    const EXAMPLE5: &[u8] =
+        b"AAAAAA\n\
+        AAABBA\n\
+        AAABBA\n\
+        ABBAAA\n\
+        ABBAAA\n\
+        AAAAAA\n";
+
+    #[test]
+    fn test_fence_price() {
+        assert_eq!(solve_from_bytes(EXAMPLE1).unwrap().0, 140);
+        assert_eq!(solve_from_bytes(EXAMPLE2).unwrap().0, 772);
+        assert_eq!(solve_from_bytes(EXAMPLE3).unwrap().0, 1930);
+    }
+
+    #[test]
+    fn test_bulk_discount_price() {
+        assert_eq!(solve_from_bytes(EXAMPLE1).unwrap().1, 80);
+        assert_eq!(solve_from_bytes(EXAMPLE2).unwrap().1, 436);
+        assert_eq!(solve_from_bytes(EXAMPLE3).unwrap().1, 1206);
+        assert_eq!(solve_from_bytes(EXAMPLE4).unwrap().1, 236);
+        assert_eq!(solve_from_bytes(EXAMPLE5).unwrap().1, 368);
+    }
+}
@@ -1,62 +1,225 @@
-use std::fs;
-
 use anyhow::Result as Result;
 
+use crate::input::read_input_to_string;
+use crate::parse_error::{parse_field, ParseError};
+use crate::solution::Solution;
+
 const TEXT_PATTERN: [[&str; 2]; 3] = [
     ["Button A: X+", ", Y+"],
     ["Button B: X+", ", Y+"],
     ["Prize: X=", ", Y="],
 ];
 
-pub fn solve_day_13(file: &str) -> Result<(i64, i64)> {
-    let data = fs::read_to_string(file)?;
+/// The prizes of part two are this much further away.
+const PRIZE_OFFSET: i64 = 10_000_000_000_000;
 
-    let mut total_presses_one = 0;
-    let mut total_presses_two = 0;
+/// Pressing button A costs 3 tokens, pressing button B costs 1 token.
+const COST_A: i64 = 3;
+const COST_B: i64 = 1;
 
-    data
-        .split("\n\n")
-        .for_each(
-            |machine| {
-                let machine_values = parse_machine(machine);
+type Vector = (i64, i64);  // (x, y)
+type Machines = Vec<Machine>;
 
-                total_presses_one += calculate_presses_for_machine(&machine_values, 0.);
-                total_presses_two += calculate_presses_for_machine(&machine_values, 1e13);
-            }
-        );
+/// The movement of the claw per press of each button and the location of the prize.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Machine {
+    pub button_a: Vector,
+    pub button_b: Vector,
+    pub prize: Vector,
+}
+
+/// The number of times each button is pressed to win the prize of a machine.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Presses {
+    pub a: i64,
+    pub b: i64,
+}
+
+impl Presses {
+    pub fn tokens(&self) -> i64 {
+        COST_A * self.a + COST_B * self.b
+    }
+}
+
+pub struct ClawContraption;
+
+impl Solution for ClawContraption {
+    type Input = Machines;
+    type PartOne = i64;
+    type PartTwo = i64;
+
+    const DAY: u8 = 13;
+    const TITLE: &'static str = "Claw Contraption";
+    const LABELS: [&'static str; 2] = ["Total Presses", "Total Presses"];
+    const INPUT_FILE: &'static str = "day_13_claw_contraption.txt";
+
+    /// Machines are separated by an empty line, keep track of the line we are at for errors.
+    fn parse(data: &[u8]) -> Result<Machines> {
+        let mut machines: Machines = Vec::new();
+        let mut line_offset: usize = 0;
+
+        // Windows line endings would hide the empty lines, the lines themselves do not mind them.
+        let data = std::str::from_utf8(data)?.replace("\r\n", "\n");
+
+        for machine in data.split("\n\n") {
+            machines.push(parse_machine(machine, line_offset)?);
+            line_offset += machine.lines().count() + 1;
+        }
+
+        Ok(machines)
+    }
+
+    fn part_one(machines: &Machines) -> Result<i64> {
+        Ok(count_tokens(machines, 0))
+    }
+
+    fn part_two(machines: &Machines) -> Result<i64> {
+        Ok(count_tokens(machines, PRIZE_OFFSET))
+    }
+}
+
+pub fn solve_day_13(file: &str) -> Result<(i64, i64)> {
+    let data = read_input_to_string(file)?;
+    solve_from_str(&data)
+}
 
-    Ok((total_presses_one, total_presses_two))
+pub fn solve_from_str(data: &str) -> Result<(i64, i64)> {
+    ClawContraption::solve_parts(data.as_bytes())
 }
 
-fn parse_machine(machine: &str) -> Vec<(f64, f64)> {
-    machine
-        .lines()
+/// The cheapest presses for each machine, None if its prize can not be won.
+///
+/// The offset is added to both coordinates of every prize.
+pub fn presses_per_machine(machines: &[Machine], offset: i64) -> Vec<Option<Presses>> {
+    machines
+        .iter()
+        .map(| machine | calculate_presses_for_machine(machine, offset))
+        .collect()
+}
+
+fn count_tokens(machines: &[Machine], offset: i64) -> i64 {
+    presses_per_machine(machines, offset)
+        .iter()
+        .flatten()
+        .map(Presses::tokens)
+        .sum()
+}
+
+/// Parse the X and Y values of both buttons and the prize, the first line is at line_offset.
+fn parse_machine(machine: &str, line_offset: usize) -> Result<Machine, ParseError> {
+    let lines: Vec<&str> = machine.lines().collect();
+    if lines.len() != TEXT_PATTERN.len() {
+        let line = lines.get(TEXT_PATTERN.len()).copied().unwrap_or_default();
+        let expected = TEXT_PATTERN.get(lines.len()).map_or("an empty line", | pat | pat[0]);
+        return Err(ParseError::new(line_offset + lines.len().min(TEXT_PATTERN.len()) + 1, 1, line, expected));
+    }
+
+    let vectors = lines
+        .iter()
         .zip(TEXT_PATTERN)
+        .enumerate()
         .map(
-            | (line, pat) | {
-                let (x,y ) = line
-                    .trim_start_matches(pat[0])
+            | (i, (line, pat)) | {
+                let i = i + line_offset;
+                let values = line
+                    .strip_prefix(pat[0])
+                    .ok_or_else(|| ParseError::in_line(i, line, line, pat[0]))?;
+                let (x, y) = values
                     .split_once(pat[1])
-                    .unwrap();
-                (x.parse().unwrap(), y.parse().unwrap())
+                    .ok_or_else(|| ParseError::in_line(i, line, values, pat[1]))?;
+
+                Ok((parse_field(i, line, x, "a number")?, parse_field(i, line, y, "a number")?))
             }
         )
-        .collect()
+        .collect::<Result<Vec<Vector>, ParseError>>()?;
+
+    Ok(Machine { button_a: vectors[0], button_b: vectors[1], prize: vectors[2] })
+}
+
+/// Solve a * A + b * B = prize exactly with Cramer's rule.
+///
+/// Only whole, non-negative numbers of presses are valid.
+/// When the buttons move in the same direction the system is singular, see `solve_collinear`.
+fn calculate_presses_for_machine(machine: &Machine, offset: i64) -> Option<Presses> {
+    let (ax, ay) = (machine.button_a.0 as i128, machine.button_a.1 as i128);
+    let (bx, by) = (machine.button_b.0 as i128, machine.button_b.1 as i128);
+    let (px, py) = ((machine.prize.0 + offset) as i128, (machine.prize.1 + offset) as i128);
+
+    let determinant = ax * by - ay * bx;
+    if determinant == 0 {
+        return solve_collinear((ax, ay), (bx, by), (px, py));
+    }
+
+    let numerator_a = px * by - py * bx;
+    let numerator_b = ax * py - ay * px;
+    if numerator_a % determinant != 0 || numerator_b % determinant != 0 {
+        return None;
+    }
+
+    let (a, b) = (numerator_a / determinant, numerator_b / determinant);
+    match a >= 0 && b >= 0 {
+        true => Some(Presses { a: a as i64, b: b as i64 }),
+        false => None
+    }
 }
 
-fn calculate_presses_for_machine(input: &[(f64, f64)], offset: f64) -> i64 {
-    let a = input[0];
-    let b = input[1];
-    let prize = (input[2].0 + offset, input[2].1 + offset);
+/// Both buttons move along the same line, so the prize must be on that line as well.
+///
+/// Solve a * u + b * v = t on a single axis, all solutions (a, b) lie on a line.
+/// With the extended Euclidean algorithm the valid values of a are a0 + k * v / gcd(u, v).
+/// The cost changes linearly along the line, so the cheapest is either the smallest or largest a.
+fn solve_collinear(button_a: (i128, i128), button_b: (i128, i128), prize: (i128, i128)) -> Option<Presses> {
+    let on_line = | (x, y): (i128, i128) | button_a.0 * y - button_a.1 * x == 0 && button_b.0 * y - button_b.1 * x == 0;
+    if !on_line(prize) {
+        return None;
+    }
+
+    let use_x = button_a.0 != 0 || button_b.0 != 0;
+    let (u, v, t) = match use_x {
+        true => (button_a.0, button_b.0, prize.0),
+        false => (button_a.1, button_b.1, prize.1)
+    };
 
-    let presses_a = (prize.0 * b.1 - b.0 * prize.1) / (b.1 * a.0 - a.1 * b.0);
-    let presses_b = (prize.1 * a.0 - prize.0 * a.1) / (a.0 * b.1 - b.0 * a.1);
+    let (a, b) = match (u, v) {
+        (0, 0) => (t == 0).then_some((0, 0))?,
+        (0, v) => (t % v == 0).then_some((0, t / v))?,
+        (u, 0) => (t % u == 0).then_some((t / u, 0))?,
+        (u, v) => {
+            let (gcd, x, _) = extended_gcd(u, v);
+            if t % gcd != 0 {
+                return None;
+            }
+
+            let step = v / gcd;
+            let a_min = (x * (t / gcd)).rem_euclid(step);
+            if a_min * u > t {
+                return None;
+            }
+
+            // Button A is cheaper per distance when it moves more than COST_A / COST_B times as far.
+            let a = match (COST_A as i128) * v < (COST_B as i128) * u {
+                true => a_min + (t / u - a_min) / step * step,
+                false => a_min
+            };
+            (a, (t - a * u) / v)
+        }
+    };
+
+    let reaches_prize = a * button_a.0 + b * button_b.0 == prize.0 && a * button_a.1 + b * button_b.1 == prize.1;
+    match a >= 0 && b >= 0 && reaches_prize {
+        true => Some(Presses { a: a as i64, b: b as i64 }),
+        false => None
+    }
+}
 
-    if presses_a.fract() > 0.0 || presses_b.fract() > 0.0 {
-        return 0;
+/// Return (gcd, x, y) such that a * x + b * y = gcd, for positive a and b.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        return (a, 1, 0);
     }
 
-    3 * (presses_a as i64) + presses_b as i64
+    let (gcd, x, y) = extended_gcd(b, a % b);
+    (gcd, y, x - (a / b) * y)
 }
 
 #[cfg(test)]
@@ -82,16 +245,51 @@ mod tests {
 
     #[test]
     fn test_example_input() {
-        let total_presses: i64 = EXAMPLE
-            .split("\n\n")
-            .map(
-                |machine| {
-                    let machine_values = parse_machine(machine);
-                    calculate_presses_for_machine(&machine_values, 0.)
-                }
-            )
-            .sum();
-
-        assert_eq!(total_presses, 480);
+        assert_eq!(solve_from_str(EXAMPLE).unwrap().0, 480);
+    }
+
+    #[test]
+    fn test_presses_per_machine() {
+        let machines = ClawContraption::parse(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(
+            presses_per_machine(&machines, 0),
+            vec![Some(Presses { a: 80, b: 40 }), None, Some(Presses { a: 38, b: 86 }), None]
+        );
+
+        let presses: Vec<bool> = presses_per_machine(&machines, PRIZE_OFFSET).iter().map(Option::is_some).collect();
+        assert_eq!(presses, vec![false, true, false, true]);
+        assert_eq!(solve_from_str(EXAMPLE).unwrap().1, 875318608908);
+    }
+
+    #[test]
+    fn test_collinear_buttons() {
+        let machine = | button_a, button_b, prize | Machine { button_a, button_b, prize };
+
+        // Button B is cheaper per distance, so press it as often as possible.
+        assert_eq!(calculate_presses_for_machine(&machine((2, 2), (4, 4), (10, 10)), 0), Some(Presses { a: 1, b: 2 }));
+        // Button A is cheaper per distance, so press it as often as possible.
+        assert_eq!(calculate_presses_for_machine(&machine((7, 7), (2, 2), (20, 20)), 0), Some(Presses { a: 2, b: 3 }));
+        // The prize is not on the line of the buttons, or can not be reached with whole presses.
+        assert_eq!(calculate_presses_for_machine(&machine((1, 1), (2, 2), (3, 4)), 0), None);
+        assert_eq!(calculate_presses_for_machine(&machine((2, 4), (4, 8), (5, 10)), 0), None);
+        assert_eq!(calculate_presses_for_machine(&machine((5, 5), (7, 7), (3, 3)), 0), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = parse_machine("Button A: X+94, Y+34\nButton B: X+22 Y+67\nPrize: X=8400, Y=5400", 4).err().unwrap();
+        assert_eq!((error.line, error.column, error.expected.as_str()), (6, 13, ", Y+"));
+
+        let error = parse_machine("Button A: X+94, Y+34\nButton B: X+22, Y+67", 0).err().unwrap();
+        assert_eq!((error.line, error.column, error.expected.as_str()), (3, 1, "Prize: X="));
+
+        let error = ClawContraption::parse(EXAMPLE.replace("X=7870", "X=78x0").as_bytes()).err().unwrap();
+        let error = error.downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (11, 10, "78x0"));
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        assert_eq!(solve_from_str(&EXAMPLE.replace('\n', "\r\n")).unwrap(), (480, 875318608908));
     }
 }
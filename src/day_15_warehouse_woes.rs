@@ -0,0 +1,297 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::Result as Result;
+
+use crate::grid::{Grid, Position};
+use crate::input::read_input;
+use crate::parse_error::ParseError;
+use crate::solution::Solution;
+
+type Move = (i32, i32);  // (dy, dx)
+
+/// The map of the warehouse, the start of the robot and the moves it tries to make.
+type WarehouseInput = (Grid<u8>, Position, Vec<Move>);
+
+/// The warehouse with the robot, boxes are either 'O' or, in the wide warehouse, '[' and ']'.
+#[derive(Clone, Debug)]
+pub struct Warehouse {
+    map: Grid<u8>,
+    robot: Position,
+}
+
+impl Warehouse {
+    /// Create the warehouse, when wide every tile is twice as wide.
+    fn new(map: &Grid<u8>, robot: Position, wide: bool) -> Self {
+        if !wide {
+            return Self { map: map.clone(), robot };
+        }
+
+        let mut wide_map = Grid::new(map.rows(), map.cols() * 2, b'.');
+        for ((row, col), &tile) in map.iter() {
+            let (left, right) = match tile {
+                b'#' => (b'#', b'#'),
+                b'O' => (b'[', b']'),
+                _ => (b'.', b'.')
+            };
+            wide_map[(row, col * 2)] = left;
+            wide_map[(row, col * 2 + 1)] = right;
+        }
+
+        Self { map: wide_map, robot: (robot.0, robot.1 * 2) }
+    }
+
+    /// Try to move the robot, pushing all boxes in front of it.
+    ///
+    /// First collect every tile that has to move, if any of them hits a wall nothing moves.
+    /// Going up or down a wide box also pushes the box half next to it, so this can become a tree.
+    /// Then move the tiles in reverse order, so the tiles furthest away move first.
+    /// Outside the map counts as a wall, so a map without walls around it does not panic.
+    pub fn step(&mut self, (dy, dx): Move) -> bool {
+        let mut to_move: Vec<Position> = vec![self.robot];
+        let mut i = 0;
+
+        while let Some(&(row, col)) = to_move.get(i) {
+            i += 1;
+            let next = (row + dy, col + dx);
+            if to_move.contains(&next) {
+                continue  // Already pushed by the other half of the same box.
+            }
+
+            match self.map.get(next).copied().unwrap_or(b'#') {
+                b'#' => return false,
+                b'O' => to_move.push(next),
+                b'[' => {
+                    to_move.push(next);
+                    if dy != 0 { to_move.push((next.0, next.1 + 1)); }
+                },
+                b']' => {
+                    to_move.push(next);
+                    if dy != 0 { to_move.push((next.0, next.1 - 1)); }
+                },
+                _ => {}
+            }
+        }
+
+        for &(row, col) in to_move.iter().skip(1).rev() {
+            self.map[(row + dy, col + dx)] = self.map[(row, col)];
+            self.map[(row, col)] = b'.';
+        }
+        self.robot = (self.robot.0 + dy, self.robot.1 + dx);
+
+        true
+    }
+
+    /// Sum of the GPS coordinates (100 * row + col) of the left edge of every box.
+    pub fn gps_sum(&self) -> usize {
+        self.map
+            .find_all(| &tile | tile == b'O' || tile == b'[')
+            .map(| (row, col) | 100 * row as usize + col as usize)
+            .sum()
+    }
+}
+
+impl Display for Warehouse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = self.map.render(
+            | position, &tile | if position == self.robot { '@' } else { tile as char }
+        );
+        write!(f, "{}", text)
+    }
+}
+
+pub struct WarehouseWoes;
+
+impl Solution for WarehouseWoes {
+    type Input = WarehouseInput;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    const DAY: u8 = 15;
+    const TITLE: &'static str = "Warehouse Woes";
+    const LABELS: [&'static str; 2] = ["Sum of GPS Coordinates", "Sum of GPS Coordinates"];
+    const INPUT_FILE: &'static str = "day_15_warehouse_woes.txt";
+
+    fn parse(data: &[u8]) -> Result<WarehouseInput> {
+        Ok(parse_warehouse(data)?)
+    }
+
+    fn part_one(input: &WarehouseInput) -> Result<usize> {
+        Ok(simulate(input, false, input.2.len()).gps_sum())
+    }
+
+    fn part_two(input: &WarehouseInput) -> Result<usize> {
+        Ok(simulate(input, true, input.2.len()).gps_sum())
+    }
+}
+
+pub fn solve_day_15(file: &str) -> Result<(usize, usize)> {
+    let data = read_input(file)?;
+    solve_from_bytes(&data)
+}
+
+pub fn solve_from_bytes(data: &[u8]) -> Result<(usize, usize)> {
+    WarehouseWoes::solve_parts(data)
+}
+
+/// The state of the (wide) warehouse after the first number of moves, e.g. to print it.
+pub fn warehouse_after_moves(data: &[u8], wide: bool, moves: usize) -> Result<Warehouse> {
+    let input = parse_warehouse(data)?;
+    Ok(simulate(&input, wide, moves))
+}
+
+/// Let the robot make the first number of moves.
+fn simulate((map, robot, moves): &WarehouseInput, wide: bool, number_of_moves: usize) -> Warehouse {
+    let mut warehouse = Warehouse::new(map, *robot, wide);
+    for step in moves.iter().take(number_of_moves) {
+        warehouse.step(*step);
+    }
+
+    warehouse
+}
+
+/// Parse the map and the moves that are separated by an empty line.
+///
+/// The moves may be spread over multiple lines.
+fn parse_warehouse(data: &[u8]) -> Result<WarehouseInput, ParseError> {
+    // The newline that ends the map, followed by an empty line, which may end with "\r\n".
+    let split = (0..data.len())
+        .find(| &i | data[i] == b'\n' && (data[i + 1..].starts_with(b"\n") || data[i + 1..].starts_with(b"\r\n")))
+        .ok_or_else(|| ParseError::at_byte(data, data.len(), "an empty line between the map and the moves"))?;
+
+    let mut map = Grid::parse_with(
+        &data[..split + 1], "'#', '.', 'O' or '@'", | char | matches!(char, b'#' | b'.' | b'O' | b'@').then_some(char)
+    )?;
+    let robot = map.take_marker(b'@', b'.')?;
+
+    let mut moves: Vec<Move> = Vec::new();
+    for (i, &char) in data.iter().enumerate().skip(split + 1) {
+        match char {
+            b'^' => moves.push((-1, 0)),
+            b'>' => moves.push((0, 1)),
+            b'v' => moves.push((1, 0)),
+            b'<' => moves.push((0, -1)),
+            b'\n' | b'\r' => {},
+            _ => return Err(ParseError::at_byte(data, i, "a move '^', '>', 'v' or '<'"))
+        }
+    }
+
+    Ok((map, robot, moves))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE1: &[u8] =
+        b"########\n\
+        #..O.O.#\n\
+        ##@.O..#\n\
+        #...O..#\n\
+        #.#.O..#\n\
+        #...O..#\n\
+        #......#\n\
+        ########\n\
+        \n\
+        <^^>>>vv<v>>v<<\n";
+
+    const EXAMPLE2: &[u8] =
+        b"##########\n\
+        #..O..O.O#\n\
+        #......O.#\n\
+        #.OO..O.O#\n\
+        #..O@..O.#\n\
+        #O#..O...#\n\
+        #O..O..O.#\n\
+        #.OO.O.OO#\n\
+        #....O...#\n\
+        ##########\n\
+        \n\
+        <vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^\n\
+        vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v\n\
+        ><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<\n\
+        <<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^\n\
+        ^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><\n\
+        ^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^\n\
+        >^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^\n\
+        <><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>\n\
+        ^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>\n\
+        v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^\n";
+
+    const EXAMPLE3: &[u8] =
+        b"#######\n\
+        #...#.#\n\
+        #.....#\n\
+        #..OO@#\n\
+        #..O..#\n\
+        #.....#\n\
+        #######\n\
+        \n\
+        <vv<<^^<<^^\n";
+
+    #[test]
+    fn test_small_warehouse() {
+        let warehouse = warehouse_after_moves(EXAMPLE1, false, usize::MAX).unwrap();
+        assert_eq!(
+            warehouse.to_string(),
+            "########\n\
+            #....OO#\n\
+            ##.....#\n\
+            #.....O#\n\
+            #.#O@..#\n\
+            #...O..#\n\
+            #...O..#\n\
+            ########\n"
+        );
+        assert_eq!(warehouse.gps_sum(), 2028);
+    }
+
+    #[test]
+    fn test_part_one() {
+        assert_eq!(solve_from_bytes(EXAMPLE2).unwrap().0, 10092);
+    }
+
+    #[test]
+    fn test_wide_warehouse() {
+        let warehouse = warehouse_after_moves(EXAMPLE3, true, 5).unwrap();
+        assert_eq!(
+            warehouse.to_string(),
+            "##############\n\
+            ##......##..##\n\
+            ##..........##\n\
+            ##...[][]...##\n\
+            ##....[]....##\n\
+            ##.....@....##\n\
+            ##############\n"
+        );
+
+        let warehouse = warehouse_after_moves(EXAMPLE3, true, usize::MAX).unwrap();
+        assert_eq!(
+            warehouse.to_string(),
+            "##############\n\
+            ##...[].##..##\n\
+            ##...@.[]...##\n\
+            ##....[]....##\n\
+            ##..........##\n\
+            ##..........##\n\
+            ##############\n"
+        );
+    }
+
+    #[test]
+    fn test_part_two() {
+        assert_eq!(solve_from_bytes(EXAMPLE2).unwrap().1, 9021);
+    }
+
+    #[test]
+    fn test_map_without_walls() {
+        let warehouse = warehouse_after_moves(b"@O.\n\n>>>\n", false, usize::MAX).unwrap();
+        assert_eq!(warehouse.to_string(), ".@O\n");
+        assert_eq!(solve_from_bytes(b"@.\n\n>>><<<^v\n").unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let data = String::from_utf8(EXAMPLE2.to_vec()).unwrap().replace('\n', "\r\n");
+        assert_eq!(solve_from_bytes(data.as_bytes()).unwrap(), (10092, 9021));
+    }
+}
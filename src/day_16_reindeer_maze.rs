@@ -1,14 +1,42 @@
 use std::cmp::Reverse;
-use std::collections::{BinaryHeap, HashMap};
-use std::fs;
+use std::collections::BinaryHeap;
+use std::fmt::{Display, Formatter};
 
-use anyhow::Result as Result;
+use anyhow::{bail, ensure, Result as Result};
+use itertools::Either;
 
-type Map = Vec<Vec<u8>>;
-type TilesVisited = Vec<(i32, i32)>;
+use crate::grid::{Grid, Position, DIRECTIONS_4};
+use crate::input::read_input;
+use crate::parse_error::ParseError;
+use crate::solution::Solution;
+
+/// The reindeer starts facing east, unless other start headings are given.
+pub const EAST: (i32, i32) = (0, 1);
+
+type Map = Grid<u8>;
+
+/// The lowest score to reach each step, per position one score for each heading in DIRECTIONS_4.
+type Scores = Grid<[u32; 4]>;
+
+/// The score of each move through the maze.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MazeCosts {
+    /// Taking a step forward.
+    pub forward: u32,
+    /// Turning 90 degrees, a turn is always followed by a step forward.
+    pub turn: u32,
+    /// Whether the reindeer may turn around, which costs two turns.
+    pub allow_reverse: bool,
+}
+
+impl Default for MazeCosts {
+    fn default() -> Self {
+        Self { forward: 1, turn: 1000, allow_reverse: false }
+    }
+}
 
 #[derive(Clone, Copy, Debug, Default, Hash, Eq, PartialEq, Ord, PartialOrd)]
-struct Step {
+pub struct Step {
     row: i32,
     col: i32,
     dy: i32,
@@ -24,8 +52,13 @@ impl Step {
         Self { row, col, dy: 0, dx: 1 }
     }
 
-    fn end(row: i32, col: i32) -> Self {
-        Self { row, col, dy: 0, dx: 0 }
+    pub fn position(&self) -> Position {
+        (self.row, self.col)
+    }
+
+    /// The direction the reindeer is facing as (dy, dx).
+    pub fn direction(&self) -> (i32, i32) {
+        (self.dy, self.dx)
     }
 
     fn new_directions(&self) -> [(i32, i32); 2] {
@@ -36,135 +69,332 @@ impl Step {
         }
     }
 
-    fn found_end(&self, end: &Step) -> bool {
-        self.col == end.col && self.row == end.row
+    /// The index of the heading in DIRECTIONS_4.
+    fn heading(&self) -> usize {
+        DIRECTIONS_4
+            .iter()
+            .position(| &direction | direction == (self.dy, self.dx))
+            .expect("a step always has a heading")
+    }
+
+    /// The directions we can turn to with the cost of turning: straight ahead, to either side or back.
+    fn turns(&self, costs: &MazeCosts) -> [Option<((i32, i32), u32)>; 4] {
+        let [left, right] = self.new_directions();
+        [
+            Some(((self.dy, self.dx), 0)),
+            Some((left, costs.turn)),
+            Some((right, costs.turn)),
+            costs.allow_reverse.then_some(((-self.dy, -self.dx), costs.turn.saturating_mul(2))),
+        ]
+    }
+
+    /// The steps that can follow this one with their cost, every turn is followed by a step forward.
+    fn next_steps(&self, costs: &MazeCosts) -> impl Iterator<Item = (Step, u32)> {
+        let (row, col, forward) = (self.row, self.col, costs.forward);
+        self.turns(costs)
+            .into_iter()
+            .flatten()
+            .map(move | ((dy, dx), turn) | (Step::new(row + dy, col + dx, dy, dx), turn.saturating_add(forward)))
+    }
+
+    /// The steps this one can follow with their cost, these all come from the tile behind us.
+    ///
+    /// Turning is symmetric, so we could have come from every direction we can turn to.
+    fn previous_steps(&self, costs: &MazeCosts) -> impl Iterator<Item = (Step, u32)> {
+        let (row, col, forward) = (self.row - self.dy, self.col - self.dx, costs.forward);
+        self.turns(costs)
+            .into_iter()
+            .flatten()
+            .map(move | ((dy, dx), turn) | (Step::new(row, col, dy, dx), turn.saturating_add(forward)))
     }
 }
 
-pub fn solve_day_16(file: &str) -> Result<(u32, usize)> {
-    let data: Vec<u8> = fs::read(file)?;
-    let (map, start, end) = create_map(&data);
-    let (shortest_route, total_steps) = search_graph(&map, &start, &end);
-
-    Ok((shortest_route, total_steps))
-}
-
-/// Create a nested vector of the map and store the start and end positions.
-fn create_map(data: &[u8]) -> (Map, Step, Step) {
-    let mut map: Map = Vec::new();
-    let mut row: Vec<u8> = Vec::new();
-
-    let mut start: Step = Step::default();
-    let mut end: Step = Step::default();
-
-    let mut x_val = 0;
-    let mut y_val = 0;
-
-    data
-        .iter()
-        .for_each(
-            | char | {
-                match char {
-                    b'\n' => {
-                        map.push(row.clone());
-                        row = Vec::new();
-                        x_val = 0; y_val += 1;
-                    },
-                    b'S' => {
-                        start = Step::start(y_val, x_val);
-                        row.push(b'.');
-                        x_val += 1
-                    }
-                    b'E' => {
-                        end = Step::end(y_val, x_val);
-                        row.push(b'.');
-                        x_val += 1
-                    }
-                    _ => {
-                        row.push(*char);
-                        x_val += 1;
+/// One of the best paths through the maze.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Route {
+    /// Every step from the start to the end, with the direction the reindeer faces on that tile.
+    pub steps: Vec<Step>,
+    pub turns: usize,
+    pub forward_moves: usize,
+}
+
+impl Route {
+    /// Count the 90 degree turns, turning around counts as two turns.
+    fn new(steps: Vec<Step>) -> Self {
+        let turns = steps
+            .windows(2)
+            .map(
+                | pair | {
+                    let ((dy_one, dx_one), (dy_two, dx_two)) = (pair[0].direction(), pair[1].direction());
+                    match (dy_one, dx_one) == (dy_two, dx_two) {
+                        true => 0,
+                        false if (dy_one, dx_one) == (-dy_two, -dx_two) => 2,
+                        false => 1
                     }
                 }
+            )
+            .sum();
+
+        Self { forward_moves: steps.len() - 1, turns, steps }
+    }
+
+    /// The score of the route, saturating at u32::MAX like the search does.
+    pub fn score(&self, costs: &MazeCosts) -> u32 {
+        let turns = u32::try_from(self.turns).unwrap_or(u32::MAX);
+        let forward_moves = u32::try_from(self.forward_moves).unwrap_or(u32::MAX);
+
+        turns.saturating_mul(costs.turn).saturating_add(forward_moves.saturating_mul(costs.forward))
+    }
+}
+
+/// The maze with all of its best paths, displayed with arrows for the direction of each step.
+#[derive(Clone, Debug)]
+pub struct BestRoutes {
+    map: Map,
+    pub routes: Vec<Route>,
+}
+
+impl Display for BestRoutes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut overlay: Grid<u8> = self.map.clone();
+        for route in &self.routes {
+            for step in &route.steps {
+                overlay[step.position()] = match step.direction() {
+                    (-1, 0) => b'^',
+                    (0, 1) => b'>',
+                    (1, 0) => b'v',
+                    _ => b'<'
+                };
             }
-        );
 
-    (map, start, end)
+            overlay[route.steps[0].position()] = b'S';
+            overlay[route.steps[route.steps.len() - 1].position()] = b'E';
+        }
+
+        write!(f, "{}", overlay)
+    }
 }
 
-/// Use Dijkstra to search the graph to find the shortest path.
-/// TODO the tiles visited cloning stuff is extremely slow! Should be improved.
-fn search_graph(map: &Map, start: &Step, end: &Step) -> (u32, usize) {
-    let mut visited: HashMap<Step, (Reverse<u32>, TilesVisited)> = HashMap::new();
-    let mut queue: BinaryHeap<(Reverse<u32>, Step, TilesVisited)> = BinaryHeap::from([(Reverse(0), *start, Vec::new())]);
-    let mut best_score: u32 = u32::MAX;
-    let mut total_tiles_visited: usize = 0;
+pub struct ReindeerMaze;
+
+impl Solution for ReindeerMaze {
+    type Input = (Map, Step, Position);
+    type PartOne = u32;
+    type PartTwo = usize;
 
-    while let Some((current_cost, current_step, mut tiles_visited)) = queue.pop() {
-        if current_cost.0 > best_score {
-            continue
+    const DAY: u8 = 16;
+    const TITLE: &'static str = "Reindeer Maze";
+    const LABELS: [&'static str; 2] = ["Lowest Score", "Tiles Visited"];
+    const INPUT_FILE: &'static str = "day_16_reindeer_maze.txt";
+
+    fn parse(data: &[u8]) -> Result<Self::Input> {
+        Ok(create_map(data)?)
+    }
+
+    fn part_one((map, start, end): &Self::Input) -> Result<u32> {
+        match lowest_score(&search_graph(map, &[*start], false, &MazeCosts::default()), *end) {
+            Some(score) => Ok(score),
+            None => bail!("the end can not be reached from the start")
         }
+    }
+
+    fn part_two((map, start, end): &Self::Input) -> Result<usize> {
+        match count_best_tiles(map, &[*start], *end, &MazeCosts::default()) {
+            Some(tiles) => Ok(tiles),
+            None => bail!("the end can not be reached from the start")
+        }
+    }
+}
 
-        match visited.get(&current_step) {
-            // Old route was better (because of Reverse we check > instead of <).
-            Some((cost, _)) if *cost > current_cost => continue,
+pub fn solve_day_16(file: &str) -> Result<(u32, usize)> {
+    let data = read_input(file)?;
+    solve_from_bytes(&data)
+}
 
-            // Found an identical route, so we have 2 paths to reach here, so combine the visited tiles.
-            Some((cost, previous_steps)) if *cost == current_cost => {
-                tiles_visited.append(&mut previous_steps.clone());
-            },
-            // New route is better or first time here.
-            _ => {visited.insert(current_step, (current_cost, tiles_visited.clone()));},
-        };
+pub fn solve_from_bytes(data: &[u8]) -> Result<(u32, usize)> {
+    ReindeerMaze::solve_parts(data)
+}
+
+/// Solve both parts with other costs, starting with any of the given headings as (dy, dx).
+///
+/// The lowest score is None when the end can not be reached, there are no best tiles then.
+pub fn solve_with(data: &[u8], costs: &MazeCosts, headings: &[(i32, i32)]) -> Result<(Option<u32>, usize)> {
+    ensure!(costs.forward > 0, "a step forward must have a cost, otherwise there can be endless best paths");
+
+    let (map, start, end) = create_map(data)?;
+    let starts = start_steps(&start, headings)?;
 
-        tiles_visited.push((current_step.row, current_step.col));
+    let best_score = lowest_score(&search_graph(&map, &starts, false, costs), end);
+    Ok((best_score, count_best_tiles(&map, &starts, end, costs).unwrap_or(0)))
+}
+
+/// Find every best path through the maze.
+pub fn best_routes(data: &[u8]) -> Result<BestRoutes> {
+    best_routes_with(data, &MazeCosts::default(), &[EAST])
+}
+
+/// Find every best path through the maze with other costs, starting with any of the given headings.
+pub fn best_routes_with(data: &[u8], costs: &MazeCosts, headings: &[(i32, i32)]) -> Result<BestRoutes> {
+    ensure!(costs.forward > 0, "a step forward must have a cost, otherwise there can be endless best paths");
 
-        if current_step.found_end(end) && current_cost.0 <= best_score {
-            best_score = current_cost.0;
+    let (map, start, end) = create_map(data)?;
+    let starts = start_steps(&start, headings)?;
+    let routes = find_best_routes(&map, &starts, end, costs);
 
-            tiles_visited.sort_unstable();
-            tiles_visited.dedup();
-            total_tiles_visited = total_tiles_visited.max(tiles_visited.len());
-            continue
+    Ok(BestRoutes { map, routes })
+}
+
+/// The reindeer starts on the same tile, facing one of the headings.
+fn start_steps(start: &Step, headings: &[(i32, i32)]) -> Result<Vec<Step>> {
+    ensure!(!headings.is_empty(), "at least one start heading is needed");
+    for heading in headings {
+        ensure!(DIRECTIONS_4.contains(heading), "the start heading {:?} is not north, east, south or west", heading);
+    }
+
+    Ok(headings.iter().map(| &(dy, dx) | Step::new(start.row, start.col, dy, dx)).collect())
+}
+
+/// Create a grid of the map and store the start and end positions.
+fn create_map(data: &[u8]) -> Result<(Map, Step, Position), ParseError> {
+    let mut map = Grid::parse_with(
+        data, "'#', '.', 'S' or 'E'", | char | matches!(char, b'#' | b'.' | b'S' | b'E').then_some(char)
+    )?;
+    let start = map.take_marker(b'S', b'.')?;
+    let end = map.take_marker(b'E', b'.')?;
+
+    Ok((map, Step::start(start.0, start.1), end))
+}
+
+/// Use Dijkstra to find the lowest score of every step that can be reached from the start steps.
+///
+/// When searching backwards the moves are reversed, so the scores are the lowest score to reach
+/// one of the start steps instead.
+fn search_graph(map: &Map, starts: &[Step], backwards: bool, costs: &MazeCosts) -> Scores {
+    let mut scores: Scores = Grid::new(map.rows(), map.cols(), [u32::MAX; 4]);
+    let mut queue: BinaryHeap<(Reverse<u32>, Step)> = BinaryHeap::new();
+
+    for start in starts {
+        scores[(start.row, start.col)][start.heading()] = 0;
+        queue.push((Reverse(0), *start));
+    }
+
+    while let Some((Reverse(score), step)) = queue.pop() {
+        if score > scores[(step.row, step.col)][step.heading()] {
+            continue  // Already reached with a lower score.
         }
 
-        // straight
-        let new_row = current_step.row + current_step.dy;
-        let new_col = current_step.col + current_step.dx;
-        if let Some(slice) = map.get(new_row as usize) {
-            if let Some(&new_position) = slice.get(new_col as usize) {
-                if new_position == b'.' {
-                    queue.push(
-                        (
-                            Reverse(current_cost.0 + 1),
-                            Step::new(new_row, new_col, current_step.dy, current_step.dx),
-                            tiles_visited.clone()
-                        )
-                    )
-                }
+        let moves = match backwards {
+            true => Either::Left(step.previous_steps(costs)),
+            false => Either::Right(step.next_steps(costs))
+        };
+
+        for (next, cost) in moves {
+            if map.get((next.row, next.col)) != Some(&b'.') {
+                continue
+            }
+
+            let next_score = score.saturating_add(cost);
+            let best = &mut scores[(next.row, next.col)][next.heading()];
+            if next_score < *best {
+                *best = next_score;
+                queue.push((Reverse(next_score), next));
             }
         }
+    }
 
-        // Turn 90 degrees
-        for turn in  current_step.new_directions() {
-            let new_row = current_step.row + turn.0;
-            let new_col = current_step.col + turn.1;
-            if let Some(slice) = map.get(new_row as usize) {
-                if let Some(&new_position) = slice.get(new_col as usize) {
-                    if new_position == b'.' {
-                        queue.push(
-                            (
-                                Reverse(current_cost.0 + 1001),
-                                Step::new(new_row, new_col, turn.0, turn.1),
-                                tiles_visited.clone()
-                            )
-                        )
-                    }
+    scores
+}
+
+/// The end can be reached with any heading, None when it can not be reached at all.
+fn lowest_score(scores: &Scores, end: Position) -> Option<u32> {
+    scores[end].iter().copied().filter(| &score | score != u32::MAX).min()
+}
+
+/// Search forward from the starts and backward from the end (with every heading).
+///
+/// Returns the scores from the start, the scores to the end and the lowest score to reach the end, if any.
+fn search_both_ways(map: &Map, starts: &[Step], end: Position, costs: &MazeCosts) -> (Scores, Scores, Option<u32>) {
+    let from_start = search_graph(map, starts, false, costs);
+    let best_score = lowest_score(&from_start, end);
+
+    let end_steps: Vec<Step> = DIRECTIONS_4.iter().map(| &(dy, dx) | Step::new(end.0, end.1, dy, dx)).collect();
+    let to_end = search_graph(map, &end_steps, true, costs);
+
+    (from_start, to_end, best_score)
+}
+
+/// A step is on a best path when its score from the start plus its score to the end is the lowest score.
+#[inline]
+fn is_on_best_path(from_start: &Scores, to_end: &Scores, best_score: u32, step: &Step) -> bool {
+    let heading = step.heading();
+    from_start[step.position()][heading].saturating_add(to_end[step.position()][heading]) == best_score
+}
+
+/// Count the tiles that are part of at least one of the best paths, None when the end can not be reached.
+fn count_best_tiles(map: &Map, starts: &[Step], end: Position, costs: &MazeCosts) -> Option<usize> {
+    let (from_start, to_end, best_score) = search_both_ways(map, starts, end, costs);
+    let best_score = best_score?;
+
+    let tiles = map
+        .positions()
+        .filter(
+            | &(row, col) | {
+                DIRECTIONS_4
+                    .iter()
+                    .any(| &(dy, dx) | is_on_best_path(&from_start, &to_end, best_score, &Step::new(row, col, dy, dx)))
+            }
+        )
+        .count();
+
+    Some(tiles)
+}
+
+/// Walk every best path with a depth first search, only following moves that stay on a best path.
+///
+/// The number of best paths can grow exponentially with the size of the maze.
+fn find_best_routes(map: &Map, starts: &[Step], end: Position, costs: &MazeCosts) -> Vec<Route> {
+    let (from_start, to_end, best_score) = search_both_ways(map, starts, end, costs);
+    let Some(best_score) = best_score else {
+        return Vec::new();
+    };
+
+    let next_on_best_path = | step: &Step | -> Vec<Step> {
+        let score = from_start[step.position()][step.heading()];
+        step.next_steps(costs)
+            .filter(| (next, cost) | {
+                map.get(next.position()) == Some(&b'.')
+                    && from_start[next.position()][next.heading()] == score.saturating_add(*cost)
+                    && is_on_best_path(&from_start, &to_end, best_score, next)
+            })
+            .map(| (next, _) | next)
+            .collect()
+    };
+
+    let mut routes: Vec<Route> = Vec::new();
+    for start in starts.iter().filter(| start | is_on_best_path(&from_start, &to_end, best_score, start)) {
+        let mut path: Vec<Step> = vec![*start];
+        let mut stack: Vec<std::vec::IntoIter<Step>> = vec![next_on_best_path(start).into_iter()];
+
+        while let Some(options) = stack.last_mut() {
+            match options.next() {
+                Some(next) if next.position() == end => {
+                    path.push(next);
+                    routes.push(Route::new(path.clone()));
+                    path.pop();
+                },
+                Some(next) => {
+                    stack.push(next_on_best_path(&next).into_iter());
+                    path.push(next);
+                },
+                None => {
+                    stack.pop();
+                    path.pop();
                 }
             }
         }
     }
 
-    (best_score, total_tiles_visited)
+    routes
 }
 
 #[cfg(test)]
@@ -209,17 +439,92 @@ mod tests {
 
     #[test]
     fn test_example_one() {
-        let (map, start, end) = create_map(EXAMPLE1);
-        let (score, steps) = search_graph(&map, &start, &end);
+        let (score, steps) = solve_from_bytes(EXAMPLE1).unwrap();
         assert_eq!(score, 7036);
         assert_eq!(steps, 45);
     }
 
     #[test]
     fn test_example_two() {
-        let (map, start, end) = create_map(EXAMPLE2);
-        let (score, steps) = search_graph(&map, &start, &end);
+        let (score, steps) = solve_from_bytes(EXAMPLE2).unwrap();
         assert_eq!(score, 11048);
         assert_eq!(steps, 64);
     }
+
+    #[test]
+    fn test_best_routes() {
+        let best = best_routes(EXAMPLE1).unwrap();
+        assert_eq!(best.routes.len(), 3);
+        for route in &best.routes {
+            assert_eq!(route.steps[0], Step::start(13, 1));
+            assert_eq!(route.steps.last().unwrap().position(), (1, 13));
+            assert_eq!(1000 * route.turns + route.forward_moves, 7036);
+        }
+
+        let best = best_routes(EXAMPLE2).unwrap();
+        assert_eq!(best.routes.len(), 2);
+        assert_eq!(
+            best.to_string(),
+            "#################\n\
+            #...#...#...#..E#\n\
+            #.#.#.#.#.#.#.#^#\n\
+            #.#.#.#...#...#^#\n\
+            #.#.#.#.###.#.#^#\n\
+            #^>>#.#.#.....#^#\n\
+            #^#v#.#.#.#####^#\n\
+            #^#v..#.#.#^>>>^#\n\
+            #^#v#####.#^###^#\n\
+            #^#v#..^>>>>#^>>#\n\
+            #^#v###^#####^###\n\
+            #^#v#^>>#..^>>#.#\n\
+            #^#v#^#####^###.#\n\
+            #^#v#^>>>>>>..#.#\n\
+            #^#v#^#########.#\n\
+            #S#v>>..........#\n\
+            #################\n"
+        );
+    }
+
+    #[test]
+    fn test_maze_costs() {
+        assert_eq!(solve_with(EXAMPLE1, &MazeCosts::default(), &[EAST]).unwrap(), (Some(7036), 45));
+
+        // Without turning costs the best paths are the shortest paths.
+        let costs = MazeCosts { forward: 1, turn: 0, allow_reverse: false };
+        assert_eq!(solve_with(EXAMPLE1, &costs, &[EAST]).unwrap(), (Some(28), 37));
+        assert!(solve_with(EXAMPLE1, &MazeCosts { forward: 0, ..costs }, &[EAST]).is_err());
+    }
+
+    #[test]
+    fn test_start_headings() {
+        let corridor = b"#####\n#S.E#\n#####\n";
+        let west = (0, -1);
+
+        assert_eq!(solve_with(corridor, &MazeCosts::default(), &[west]).unwrap(), (None, 0));
+        assert_eq!(solve_with(corridor, &MazeCosts::default(), &[west, EAST]).unwrap(), (Some(2), 3));
+
+        let costs = MazeCosts { allow_reverse: true, ..MazeCosts::default() };
+        let best = best_routes_with(corridor, &costs, &[west]).unwrap();
+        assert_eq!(best.routes.len(), 1);
+        assert_eq!((best.routes[0].turns, best.routes[0].forward_moves), (2, 2));
+        assert_eq!(best.routes[0].score(&costs), 2002);
+        assert_eq!(best.routes[0].score(&MazeCosts { turn: u32::MAX, ..costs }), u32::MAX);
+        assert_eq!(best.to_string(), "#####\n#S>E#\n#####\n");
+
+        assert!(solve_with(corridor, &costs, &[(1, 1)]).is_err());
+        assert!(best_routes_with(corridor, &MazeCosts { forward: 0, ..costs }, &[west]).is_err());
+    }
+
+    #[test]
+    fn test_unreachable_end() {
+        let walled = b"#####\n#S#E#\n#####\n";
+
+        let input = ReindeerMaze::parse(walled).unwrap();
+        assert!(ReindeerMaze::part_one(&input).is_err());
+        assert!(ReindeerMaze::part_two(&input).is_err());
+        assert!(solve_from_bytes(walled).is_err());
+
+        assert_eq!(solve_with(walled, &MazeCosts::default(), &[EAST]).unwrap(), (None, 0));
+        assert!(best_routes(walled).unwrap().routes.is_empty());
+    }
 }
@@ -0,0 +1,264 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+
+use crate::parse_error::ParseError;
+
+/// A position in the grid as (row, col), may be out of bounds.
+pub type Position = (i32, i32);
+
+/// The 4-connected directions as (dy, dx).
+pub const DIRECTIONS_4: [(i32, i32); 4] = [
+    (-1, 0),  // North
+    (0, 1),   // East
+    (1, 0),   // South
+    (0, -1)   // West
+];
+
+/// The 8-connected directions as (dy, dx).
+pub const DIRECTIONS_8: [(i32, i32); 8] = [
+    (-1, -1),  // North West
+    (-1, 0),   // North
+    (-1, 1),   // North East
+    (0, -1),   // West
+    (0, 1),    // East
+    (1, -1),   // South West
+    (1, 0),    // South
+    (1, 1)     // South East
+];
+
+/// A rectangular grid stored row by row in a single vector.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(rows: usize, cols: usize, value: T) -> Self where T: Clone {
+        Self { cells: vec![value; rows * cols], rows, cols }
+    }
+
+    /// Parse newline separated rows of bytes, converting each byte to a cell.
+    ///
+    /// Bytes for which `convert` returns None are reported as a ParseError with the `expected` text.
+    /// All rows must have the same length, a trailing newline is optional and rows may end with `\r\n`.
+    pub fn parse_with(
+        data: &[u8], expected: &str, mut convert: impl FnMut(u8) -> Option<T>
+    ) -> Result<Self, ParseError> {
+        let lines: Vec<&[u8]> = data
+            .strip_suffix(b"\n")
+            .unwrap_or(data)
+            .split(| &byte | byte == b'\n')
+            .map(| line | line.strip_suffix(b"\r").unwrap_or(line))
+            .collect();
+        let cols = lines[0].len();
+        if cols == 0 {
+            return Err(ParseError::new(1, 1, "", expected));
+        }
+
+        let mut cells: Vec<T> = Vec::with_capacity(lines.len() * cols);
+        for (row, line) in lines.iter().enumerate() {
+            if line.len() != cols {
+                let column = line.len().min(cols) + 1;
+                let text = String::from_utf8_lossy(&line[column - 1..]);
+                return Err(ParseError::new(row + 1, column, &text, &format!("a row of {} columns", cols)));
+            }
+
+            for (col, &byte) in line.iter().enumerate() {
+                match convert(byte) {
+                    Some(cell) => cells.push(cell),
+                    None => return Err(ParseError::new(row + 1, col + 1, &(byte as char).to_string(), expected))
+                }
+            }
+        }
+
+        Ok(Self { cells, rows: lines.len(), cols })
+    }
+
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    #[inline]
+    pub fn contains(&self, (row, col): Position) -> bool {
+        row >= 0 && col >= 0 && (row as usize) < self.rows && (col as usize) < self.cols
+    }
+
+    #[inline]
+    pub fn get(&self, position: Position) -> Option<&T> {
+        match self.contains(position) {
+            true => Some(&self.cells[self.index(position)]),
+            false => None
+        }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        match self.contains(position) {
+            true => {
+                let index = self.index(position);
+                Some(&mut self.cells[index])
+            },
+            false => None
+        }
+    }
+
+    /// Create a new grid of the same size by converting each cell.
+    pub fn map<U>(&self, convert: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { cells: self.cells.iter().map(convert).collect(), rows: self.rows, cols: self.cols }
+    }
+
+    /// Iterate over all positions, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let cols = self.cols;
+        (0..self.rows * self.cols).map(move | i | ((i / cols) as i32, (i % cols) as i32))
+    }
+
+    /// Iterate over all positions and cells, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// Iterate over the positions of the cells that match the predicate.
+    pub fn find_all<'a>(&'a self, predicate: impl Fn(&T) -> bool + 'a) -> impl Iterator<Item = Position> + 'a {
+        self.iter().filter(move | (_, cell) | predicate(cell)).map(| (position, _) | position)
+    }
+
+    /// The 4-connected neighbors that are within the grid.
+    pub fn neighbors(&self, (row, col): Position) -> impl Iterator<Item = Position> + '_ {
+        DIRECTIONS_4
+            .iter()
+            .map(move | (dy, dx) | (row + dy, col + dx))
+            .filter(| position | self.contains(*position))
+    }
+
+    /// The 8-connected neighbors that are within the grid.
+    pub fn neighbors_diagonal(&self, (row, col): Position) -> impl Iterator<Item = Position> + '_ {
+        DIRECTIONS_8
+            .iter()
+            .map(move | (dy, dx) | (row + dy, col + dx))
+            .filter(| position | self.contains(*position))
+    }
+
+    /// Render the grid as text, with a character for each cell.
+    pub fn render(&self, mut to_char: impl FnMut(Position, &T) -> char) -> String {
+        let mut text = String::with_capacity(self.rows * (self.cols + 1));
+        for (position, cell) in self.iter() {
+            text.push(to_char(position, cell));
+            if position.1 as usize == self.cols - 1 {
+                text.push('\n');
+            }
+        }
+
+        text
+    }
+
+    #[inline]
+    fn index(&self, (row, col): Position) -> usize {
+        row as usize * self.cols + col as usize
+    }
+}
+
+impl Grid<u8> {
+    /// Parse newline separated rows of bytes.
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        Self::parse_with(data, "a byte", Some)
+    }
+
+    /// Find the single occurrence of a marker (like the start 'S'), replace it and return the position.
+    pub fn take_marker(&mut self, marker: u8, replacement: u8) -> Result<Position, ParseError> {
+        let markers: Vec<Position> = self.find_all(| &cell | cell == marker).take(2).collect();
+        let expected = format!("a single '{}'", marker as char);
+
+        match markers[..] {
+            [position] => {
+                self[position] = replacement;
+                Ok(position)
+            },
+            [] => Err(ParseError::new(self.rows + 1, 1, "", &expected)),
+            [_, (row, col), ..] => {
+                Err(ParseError::new(row as usize + 1, col as usize + 1, &(marker as char).to_string(), &expected))
+            }
+        }
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        self.get(position).expect("position out of bounds")
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        self.get_mut(position).expect("position out of bounds")
+    }
+}
+
+impl Display for Grid<u8> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(| _, &cell | cell as char))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &[u8] =
+        b"#S.\n\
+        .#E\n";
+
+    #[test]
+    fn test_parse_and_render() {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid.get((1, 2)), Some(&b'E'));
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get((0, -1)), None);
+        assert_eq!(grid.to_string().as_bytes(), EXAMPLE);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = Grid::parse(b"#S.\n.#\n").err().unwrap();
+        assert_eq!((error.line, error.column), (2, 3));
+
+        let error = Grid::parse_with(b"#S.\n.#E\n", "'#' or '.'", | byte | (byte != b'E').then_some(byte)).err().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 3, "E"));
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let grid = Grid::parse(b"#S.\r\n.#E\r\n").unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid.to_string().as_bytes(), EXAMPLE);
+    }
+
+    #[test]
+    fn test_take_marker() {
+        let mut grid = Grid::parse(EXAMPLE).unwrap();
+        assert_eq!(grid.take_marker(b'S', b'.'), Ok((0, 1)));
+        assert_eq!(grid[(0, 1)], b'.');
+        assert_eq!(grid.take_marker(b'S', b'.').err().unwrap().line, 3);
+        assert_eq!(grid.take_marker(b'#', b'.').err().unwrap().line, 2);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+        assert_eq!(grid.neighbors((0, 0)).collect::<Vec<_>>(), vec![(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbors_diagonal((0, 0)).collect::<Vec<_>>(), vec![(0, 1), (1, 0), (1, 1)]);
+        assert_eq!(grid.neighbors((1, 1)).count(), 3);
+        assert_eq!(grid.neighbors_diagonal((1, 1)).count(), 5);
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// The path that reads the puzzle input from stdin.
+pub const STDIN: &str = "-";
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+/// Extensions of compressed inputs, tried in order when the plain input does not exist.
+const COMPRESSED_EXTENSIONS: [&str; 2] = ["gz", "zst"];
+
+/// Open the puzzle input in a file, or stdin for `-`, to read it piece by piece.
+///
+/// Gzip and zstd compressed input is recognized by its magic bytes and decompressed while reading.
+pub fn open_input(path: &str) -> io::Result<Box<dyn BufRead>> {
+    match path {
+        STDIN => decompress(io::stdin().lock()),
+        _ => decompress(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Read the whole puzzle input, see `open_input`.
+pub fn read_input(path: &str) -> io::Result<Vec<u8>> {
+    let mut data: Vec<u8> = Vec::new();
+    open_input(path)?.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Read the puzzle input as text, see `open_input`.
+pub fn read_input_to_string(path: &str) -> io::Result<String> {
+    let mut data = String::new();
+    open_input(path)?.read_to_string(&mut data)?;
+    Ok(data)
+}
+
+/// The plain file when it exists, else the first compressed version that exists, e.g. `day_09.txt.gz`.
+///
+/// When none exist the plain file is returned, so the error names the expected file.
+pub fn find_input(path: &Path) -> PathBuf {
+    if path.exists() {
+        return path.to_path_buf()
+    }
+
+    COMPRESSED_EXTENSIONS
+        .iter()
+        .map(| extension | PathBuf::from(format!("{}.{}", path.display(), extension)))
+        .find(| compressed | compressed.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Wrap gzip and zstd data in a decoder, other data is read as is.
+#[cfg(feature = "compressed")]
+fn decompress<R: BufRead + 'static>(mut reader: R) -> io::Result<Box<dyn BufRead>> {
+    let start = reader.fill_buf()?;
+
+    if start.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader))))
+    } else if start.starts_with(ZSTD_MAGIC) {
+        Ok(Box::new(BufReader::new(zstd::stream::read::Decoder::with_buffer(reader)?)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Without the `compressed` feature compressed data is an error instead of garbage input.
+#[cfg(not(feature = "compressed"))]
+fn decompress<R: BufRead + 'static>(mut reader: R) -> io::Result<Box<dyn BufRead>> {
+    let start = reader.fill_buf()?;
+
+    match start.starts_with(GZIP_MAGIC) || start.starts_with(ZSTD_MAGIC) {
+        true => Err(io::Error::new(io::ErrorKind::Unsupported, "compressed input needs the `compressed` feature")),
+        false => Ok(Box::new(reader)),
+    }
+}
+
+#[cfg(all(test, feature = "compressed"))]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+
+    use super::*;
+
+    const EXAMPLE: &[u8] = b"3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+
+    #[test]
+    fn test_read_compressed_input() {
+        let dir = env::temp_dir().join(format!("aoc_read_input_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let plain = dir.join("day_01.txt");
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(EXAMPLE).unwrap();
+        fs::write(dir.join("day_01.txt.gz"), gzip.finish().unwrap()).unwrap();
+        fs::write(dir.join("day_01.txt.zst"), zstd::encode_all(EXAMPLE, 0).unwrap()).unwrap();
+
+        let gz = find_input(&plain);
+        assert_eq!(gz, dir.join("day_01.txt.gz"));
+        assert_eq!(read_input(gz.to_str().unwrap()).unwrap(), EXAMPLE);
+
+        fs::remove_file(&gz).unwrap();
+        let zst = find_input(&plain);
+        assert_eq!(zst, dir.join("day_01.txt.zst"));
+        assert_eq!(read_input(zst.to_str().unwrap()).unwrap(), EXAMPLE);
+        assert_eq!(open_input(zst.to_str().unwrap()).unwrap().lines().count(), 6);
+
+        fs::write(&plain, EXAMPLE).unwrap();
+        assert_eq!(find_input(&plain), plain);
+        assert_eq!(read_input_to_string(plain.to_str().unwrap()).unwrap().as_bytes(), EXAMPLE);
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(find_input(&plain), plain);
+        assert_eq!(read_input(plain.to_str().unwrap()).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -1,6 +1,22 @@
-use std::path::Path;
-use std::time::Instant;
+use std::any::Any;
+use std::env;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
+use std::time::{Duration, Instant};
 
+use anyhow::{anyhow, bail, Result as Result};
+use cpu_time::ProcessTime;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::output::Format;
+use crate::solution::{DiagnoseOptions, Puzzle, Solved};
+
+pub mod answers;
+pub mod bench;
 pub mod day_01_historian_hysteria;
 pub mod day_02_red_nosed_reports;
 pub mod day_03_mull_it_over;
@@ -14,221 +30,396 @@ pub mod day_10_hoof_it;
 pub mod day_11_plutonian_pebbles;
 pub mod day_12_garden_groups;
 pub mod day_13_claw_contraption;
+pub mod day_15_warehouse_woes;
 pub mod day_16_reindeer_maze;
+pub mod grid;
+pub mod input;
+pub mod output;
+pub mod parse_error;
+pub mod solution;
+#[cfg(test)]
+mod test_random;
 
-pub fn solve_all() {
-    println!("Advent of Code 2024\n");
-
-    day_01();
-    day_02();
-    day_03();
-    day_04();
-    day_05();
-    day_06();
-    day_07();
-    day_08();
-    day_09();
-    day_10();
-    day_11();
-    day_12();
-    day_13();
-    
-    
-    day_16();
-}
-
-fn day_01() {
-    let file = Path::new("./data/day_01_historian_hysteria.txt").to_str().unwrap();
-    let timer = Instant::now();
-    let value = day_01_historian_hysteria::solve_day_01(file).unwrap();
-
-    println!(
-        "Day 1: Historian Hysteria\n\
-        Run Time: {:?}\n\
-        Sum of Distances 1: {}\n\
-        Similarity Score 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
+const DATA_DIR: &str = "./data";
+/// Environment variable with the data directory, used when no directory is given on the command line.
+pub const DATA_DIR_VAR: &str = "AOC_DATA_DIR";
+/// Name of the input set directly in the data directory.
+pub const DEFAULT_SET: &str = "default";
+
+/// The number of days being run by `try_run_day`, the panic hook is quiet while any are running.
+static QUIET_PANICS: AtomicUsize = AtomicUsize::new(0);
+
+/// Every solved day, in calendar order.
+pub static SOLUTIONS: [&dyn Puzzle; 15] = [
+    &day_01_historian_hysteria::HistorianHysteria,
+    &day_02_red_nosed_reports::RedNosedReports,
+    &day_03_mull_it_over::MullItOver,
+    &day_04_ceres_search::CeresSearch,
+    &day_05_print_queue::PrintQueue,
+    &day_06_guard_gallivant::GuardGallivant,
+    &day_07_bridge_repair::BridgeRepair,
+    &day_08_resonant_collinearity::ResonantCollinearity,
+    &day_09_disk_fragmenter::DiskFragmenter,
+    &day_10_hoof_it::HoofIt,
+    &day_11_plutonian_pebbles::PlutonianPebbles,
+    &day_12_garden_groups::GardenGroups,
+    &day_13_claw_contraption::ClawContraption,
+    &day_15_warehouse_woes::WarehouseWoes,
+    &day_16_reindeer_maze::ReindeerMaze,
+];
+
+/// Find the solution for a day in the registry.
+pub fn find_solution(day: u8) -> Option<&'static dyn Puzzle> {
+    SOLUTIONS
+        .iter()
+        .find(| puzzle | puzzle.day() == day)
+        .copied()
 }
 
-fn day_02() {
-    let file = Path::new("./data/day_02_red_nosed_reports.txt").to_str().unwrap();
-    let timer = Instant::now();
-    let value = day_02_red_nosed_reports::solve_day_02(file).unwrap();
-
-    println!(
-        "Day 2: Red-Nodes Reports\n\
-        Run Time: {:?}\n\
-        Safe Reports 1: {}\n\
-        Safe Reports 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
+/// The data directory: the given directory, else the one in $AOC_DATA_DIR, else `./data`.
+pub fn data_dir(dir: Option<&str>) -> PathBuf {
+    dir
+        .map(PathBuf::from)
+        .or_else(|| env::var_os(DATA_DIR_VAR).map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from(DATA_DIR))
 }
 
-fn day_03() {
-    let file = Path::new("./data/day_03_mull_it_over.txt").to_str().unwrap();
-    let timer = Instant::now();
-    let value = day_03_mull_it_over::solve_day_03(file).unwrap();
-
-    println!(
-        "Day 3: Mull It Over\n\
-        Run Time: {:?}\n\
-        Sum of instructions 1: {}\n\
-        Sum of instructions 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
+/// The default location of the puzzle input.
+pub fn default_input(puzzle: &dyn Puzzle) -> String {
+    InputSet::default_set(&data_dir(None)).input(puzzle)
 }
 
-fn day_04() {
-    let file = Path::new("./data/day_04_ceres_search.txt").to_str().unwrap();
-    let timer = Instant::now();
-    let value = day_04_ceres_search::solve_day_04(file).unwrap();
-
-    println!(
-        "Day 4: Ceres Search\n\
-        Run Time: {:?}\n\
-        XMAS Count 1: {}\n\
-        X-MAS Count 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
+/// A named set of puzzle inputs, e.g. the inputs of one team member.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InputSet {
+    pub name: String,
+    pub dir: PathBuf,
 }
 
-fn day_05() {
-    let file = Path::new("./data/day_05_print_queue.txt").to_str().unwrap();
-    let timer = Instant::now();
-    let value = day_05_print_queue::solve_day_05(file).unwrap();
-
-    println!(
-        "Day 5: Print Queue\n\
-        Run Time: {:?}\n\
-        Ordered Pages Sum 1: {}\n\
-        Unordered Pages Sum 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
+impl InputSet {
+    /// The inputs directly in the data directory.
+    pub fn default_set(data_dir: &Path) -> Self {
+        Self { name: DEFAULT_SET.to_string(), dir: data_dir.to_path_buf() }
+    }
+
+    /// The inputs in a subdirectory of the data directory with the name of the set.
+    pub fn named(data_dir: &Path, name: &str) -> Self {
+        Self { name: name.to_string(), dir: data_dir.join(name) }
+    }
+
+    /// The named sets, or only the default set when no names are given.
+    pub fn from_names(data_dir: &Path, names: &[String]) -> Vec<Self> {
+        match names.is_empty() {
+            true => vec![Self::default_set(data_dir)],
+            false => names.iter().map(| name | Self::named(data_dir, name)).collect()
+        }
+    }
+
+    /// The location of the puzzle input in this set, which may also be compressed.
+    pub fn input(&self, puzzle: &dyn Puzzle) -> String {
+        input::find_input(&self.dir.join(puzzle.input_file())).to_string_lossy().into_owned()
+    }
 }
 
-fn day_06() {
-    let file = Path::new("./data/day_06_guard_gallivant.txt").to_str().unwrap();
-    let timer = Instant::now();
-    let value = day_06_guard_gallivant::solve_day_06(file).unwrap();
-
-    println!(
-        "Day 6: Guard Gallivant\n\
-        Run Time: {:?}\n\
-        Tiles Covered 1: {}\n\
-        Objects To Cause Loops 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
+/// The answers and total run time of a single day.
+#[derive(Clone, Debug)]
+pub struct DayResult {
+    pub day: u8,
+    pub title: &'static str,
+    /// Time to read the input and solve, including parsing.
+    pub elapsed: Duration,
+    pub solved: Solved,
 }
 
-fn day_07() {
-    let file = Path::new("./data/day_07_bridge_repair.txt").to_str().unwrap();
-    let timer = Instant::now();
-    let value = day_07_bridge_repair::solve_day_07(file).unwrap();
-
-    println!(
-        "Day 7: Bridge Repair\n\
-        Run Time: {:?}\n\
-        Total Calibration Result 1: {}\n\
-        Total Calibration Result 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
+/// Why a day could not be solved.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Failure {
+    /// The input file does not exist.
+    InputMissing(String),
+    /// The input could not be read or decompressed, or does not match the format of the puzzle.
+    ParseError(String),
+    /// The solution panicked, with the panic message.
+    Panic(String),
 }
 
-fn day_08() {
-    let file = Path::new("./data/day_08_resonant_collinearity.txt").to_str().unwrap();
-    let timer = Instant::now();
-    let value = day_08_resonant_collinearity::solve_day_08(file).unwrap();
-
-    println!(
-        "Day 8: Resonant Collinearity\n\
-        Run Time: {:?}\n\
-        # Antinodes 1: {}\n\
-        # Antinodes 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
+impl Failure {
+    /// Short description for the summary table.
+    pub fn status(&self) -> &'static str {
+        match self {
+            Failure::InputMissing(_) => "input missing",
+            Failure::ParseError(_) => "parse error",
+            Failure::Panic(_) => "panic caught",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Failure::InputMissing(message) | Failure::ParseError(message) | Failure::Panic(message) => message
+        }
+    }
 }
 
-fn day_09() {
-    let file = Path::new("./data/day_09_disk_fragmenter.txt").to_str().unwrap();
-    let timer = Instant::now();
-    let value = day_09_disk_fragmenter::solve_day_09(file).unwrap();
-
-    println!(
-        "Day 9: Disk Fragmenter\n\
-        Run Time: {:?}\n\
-        Checksum 1: {}\n\
-        ... 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
+/// The result of a day when running every day, a failing day does not stop the run.
+#[derive(Clone, Debug)]
+pub struct DayOutcome {
+    pub day: u8,
+    pub title: &'static str,
+    pub result: std::result::Result<DayResult, Failure>,
 }
 
-fn day_10() {
-    let file = Path::new("./data/day_10_hoof_it.txt").to_str().unwrap();
+/// Solve every day and print the answers, followed by a summary of the days that could not be solved.
+///
+/// In parallel the days, and the days that can use multiple threads themselves, are solved on a thread pool
+/// and printed in day order once all are done, otherwise each day is printed as soon as it is solved.
+/// Returns the number of days that failed.
+pub fn solve_all(format: Format, parallel: bool, set: &InputSet) -> usize {
+    if format == Format::Text {
+        match set.name.as_str() {
+            DEFAULT_SET => println!("Advent of Code 2024\n"),
+            name => println!("Advent of Code 2024 (input set {})\n", name),
+        }
+    }
+
     let timer = Instant::now();
-    let value = day_10_hoof_it::solve_day_10(file).unwrap();
-
-    println!(
-        "Day 10: Hoof It\n\
-        Run Time: {:?}\n\
-        Hiking Trails Score 1: {}\n\
-        Hiking Trails Rating 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
+    let cpu_timer = ProcessTime::now();
+    let outcomes: Vec<DayOutcome> = match parallel {
+        true => {
+            let outcomes = map_items(&SOLUTIONS, true, | puzzle | try_run_day(*puzzle, set, true));
+            if format == Format::Text {
+                outcomes.iter().for_each(| outcome | println!("{}", output::format_outcome(outcome)));
+            }
+            outcomes
+        },
+        false => {
+            SOLUTIONS
+                .iter()
+                .map(
+                    | puzzle | {
+                        let outcome = try_run_day(*puzzle, set, false);
+                        if format == Format::Text {
+                            println!("{}", output::format_outcome(&outcome));
+                        }
+                        outcome
+                    }
+                )
+                .collect()
+        }
+    };
+    let elapsed = timer.elapsed();
+    let cpu_time = cpu_timer.elapsed();
+
+    let results: Vec<DayResult> = outcomes
+        .iter()
+        .filter_map(| outcome | outcome.result.as_ref().ok().cloned())
+        .collect();
+    let failed = outcomes.len() - results.len();
+
+    // The machine-readable formats only contain the solved days, the summary goes to stderr.
+    match format {
+        Format::Text => print!("{}\n{}", output::format_total(elapsed, cpu_time), output::format_summary(&outcomes)),
+        _ => {
+            print_results(&results, format);
+            if failed > 0 {
+                eprint!("{}", output::format_summary(&outcomes));
+            }
+        },
+    }
+
+    failed
 }
 
-fn day_11() {
-    let file = Path::new("./data/day_11_plutonian_pebbles.txt").to_str().unwrap();
-    let timer = Instant::now();
-    let value = day_11_plutonian_pebbles::solve_day_11(file).unwrap();
-
-    println!(
-        "Day 11: Plutonian Pebbles\n\
-        Run Time: {:?}\n\
-        Total Pebbles 1: {}\n\
-        Total Pebbles 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
+/// Map every item on the thread pool when `parallel` is set, else one after the other, the results stay in order.
+#[cfg(feature = "parallel")]
+pub(crate) fn map_items<T: Sync, R: Send>(items: &[T], parallel: bool, f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    match parallel {
+        true => items.par_iter().map(f).collect(),
+        false => items.iter().map(f).collect(),
+    }
 }
 
-fn day_12() {
-    let file = Path::new("./data/day_12_garden_groups.txt").to_str().unwrap();
-    let timer = Instant::now();
-    let value = day_12_garden_groups::solve_day_12(file).unwrap();
-    
-    println!(
-        "Day 12: Garden Groups\n\
-        Run Time: {:?}\n\
-        ... 1: {}\n\
-        ... 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
+/// Without the `parallel` feature the items are always mapped one after the other.
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn map_items<T, R>(items: &[T], _parallel: bool, f: impl Fn(&T) -> R) -> Vec<R> {
+    items.iter().map(f).collect()
 }
 
-fn day_13() {
-    let file = Path::new("./data/day_13_claw_contraption.txt").to_str().unwrap();
-    let timer = Instant::now();
-    let value = day_13_claw_contraption::solve_day_13(file).unwrap();
-
-    println!(
-        "Day 13: Claw Contraption\n\
-        Run Time: {:?}\n\
-        Total Presses 1: {}\n\
-        Total Presses 2: {}\n",
-        timer.elapsed(), value.0, value.1
+/// Solve a day with the input of the set, catching errors and panics.
+///
+/// Only an input file that does not exist is a missing input, any other error is a parse error.
+/// A panic is not printed, its message is part of the outcome instead.
+pub fn try_run_day(puzzle: &dyn Puzzle, set: &InputSet, parallel: bool) -> DayOutcome {
+    let file = set.input(puzzle);
+
+    let outcome = with_quiet_panics(|| panic::catch_unwind(AssertUnwindSafe(|| run_day(puzzle, None, Some(&file), parallel))));
+
+    let result = match outcome {
+        Ok(Ok(result)) => Ok(result),
+        Ok(Err(error)) if is_not_found(&error) => Err(Failure::InputMissing(format!("{}: {}", file, error))),
+        Ok(Err(error)) => Err(Failure::ParseError(format!("{}: {:#}", file, error))),
+        Err(payload) => Err(Failure::Panic(panic_message(payload))),
+    };
+
+    DayOutcome { day: puzzle.day(), title: puzzle.title(), result }
+}
+
+fn is_not_found(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<io::Error>()
+        .is_some_and(| error | error.kind() == io::ErrorKind::NotFound)
+}
+
+/// Run the closure without the panic hook printing panics, also those on other threads.
+///
+/// The hook is replaced once by one that only calls the original hook while no closure is running,
+/// so days running in parallel do not restore each other's hook.
+fn with_quiet_panics<R>(f: impl FnOnce() -> R) -> R {
+    static INSTALL_HOOK: Once = Once::new();
+    INSTALL_HOOK.call_once(
+        || {
+            let hook = panic::take_hook();
+            panic::set_hook(Box::new(
+                move | info | {
+                    if QUIET_PANICS.load(Ordering::SeqCst) == 0 {
+                        hook(info)
+                    }
+                }
+            ));
+        }
     );
+
+    QUIET_PANICS.fetch_add(1, Ordering::SeqCst);
+    let result = f();
+    QUIET_PANICS.fetch_sub(1, Ordering::SeqCst);
+    result
+}
+
+/// The message of a panic, when it has one.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map_or_else(|| "unknown panic".to_string(), | message | message.to_string())
+    }
 }
 
-fn day_16() {
-    let file = Path::new("./data/day_16_reindeer_maze.txt").to_str().unwrap();
+/// Solve a single day and print the answers.
+///
+/// When no part is given both parts are printed.
+/// When no input file is given the file for that day in the input set is used.
+pub fn solve_day(
+    day: u8, part: Option<u8>, input: Option<&str>, format: Format, set: &InputSet, parallel: bool
+) -> Result<()> {
+    let puzzle = find_solution(day).ok_or_else(|| anyhow!("Day {} has not been solved (yet)", day))?;
+    let file = input.map_or_else(|| set.input(puzzle), String::from);
+    let result = run_day(puzzle, part, Some(&file), parallel)?;
+
+    match format {
+        Format::Text => println!("{}", output::format_text(&result)),
+        _ => print_results(&[result], format),
+    }
+
+    Ok(())
+}
+
+/// Print the diagnostics of a day, for the days that have them.
+///
+/// When no input file is given the file for that day in the input set is used.
+pub fn diagnose_day(day: u8, input: Option<&str>, set: &InputSet, options: &DiagnoseOptions) -> Result<()> {
+    let puzzle = find_solution(day).ok_or_else(|| anyhow!("Day {} has not been solved (yet)", day))?;
+    let file = input.map_or_else(|| set.input(puzzle), String::from);
+
+    match puzzle.diagnose(&input::read_input(&file)?, options)? {
+        Some(diagnostics) => print!("{}", diagnostics),
+        None => bail!("Day {} has no diagnostics", day),
+    }
+
+    Ok(())
+}
+
+/// Read the input and solve a day, the input may be `-` for stdin.
+///
+/// With `parallel` the days that can use multiple threads solve on the thread pool.
+pub fn run_day(puzzle: &dyn Puzzle, part: Option<u8>, input: Option<&str>, parallel: bool) -> Result<DayResult> {
+    let file = input.map_or_else(|| default_input(puzzle), String::from);
+
     let timer = Instant::now();
-    let value = day_16_reindeer_maze::solve_day_16(file).unwrap();
-
-    println!(
-        "Day 16: Reindeer Maze\n\
-        Run Time: {:?}\n\
-        Lowest Score 1: {}\n\
-        Tiles Visisted 2: {}\n",
-        timer.elapsed(), value.0, value.1
-    );
-}
\ No newline at end of file
+    let solved = puzzle.solve_reader(&mut input::open_input(&file)?, part, parallel)?;
+
+    Ok(DayResult { day: puzzle.day(), title: puzzle.title(), elapsed: timer.elapsed(), solved })
+}
+
+/// Print the machine-readable formats, text is printed per day instead.
+fn print_results(results: &[DayResult], format: Format) {
+    match format {
+        Format::Text => {},
+        Format::Json => println!("{}", output::format_json(results)),
+        Format::Csv => print!("{}", output::format_csv(results)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::solution::Solution;
+
+    /// A puzzle that panics on an empty input and fails to parse anything but digits.
+    struct Fragile;
+
+    impl Solution for Fragile {
+        type Input = Vec<u8>;
+        type PartOne = u8;
+        type PartTwo = u8;
+
+        const DAY: u8 = 25;
+        const TITLE: &'static str = "Fragile";
+        const LABELS: [&'static str; 2] = ["First", "Last"];
+        const INPUT_FILE: &'static str = "fragile.txt";
+
+        fn parse(data: &[u8]) -> Result<Self::Input> {
+            match data.iter().all(u8::is_ascii_digit) {
+                true => Ok(data.to_vec()),
+                false => Err(anyhow!("expected only digits")),
+            }
+        }
+
+        fn part_one(input: &Self::Input) -> Result<u8> {
+            Ok(input[0])
+        }
+
+        fn part_two(input: &Self::Input) -> Result<u8> {
+            Ok(input[input.len() - 1])
+        }
+    }
+
+    #[test]
+    fn test_try_run_day() {
+        let dir = env::temp_dir().join(format!("aoc_try_run_day_{}", std::process::id()));
+        let set = InputSet::default_set(&dir);
+
+        let outcome = try_run_day(&Fragile, &set, false);
+        assert!(matches!(outcome.result, Err(Failure::InputMissing(_))));
+
+        fs::create_dir_all(&dir).unwrap();
+        let mut statuses: Vec<&str> = Vec::new();
+        // Corrupt gzip data and a directory instead of a file can be opened, but not read.
+        for data in [&b"12"[..], b"1a", b"", b"\x1f\x8b\x08", b"dir"] {
+            match data {
+                b"dir" => {
+                    fs::remove_file(set.input(&Fragile)).unwrap();
+                    fs::create_dir(set.input(&Fragile)).unwrap();
+                },
+                _ => fs::write(set.input(&Fragile), data).unwrap(),
+            }
+            match try_run_day(&Fragile, &set, false).result {
+                Ok(_) => statuses.push("OK"),
+                Err(failure) => statuses.push(failure.status()),
+            }
+        }
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(statuses, vec!["OK", "parse error", "panic caught", "parse error", "parse error"]);
+    }
+}
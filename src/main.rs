@@ -1,5 +1,219 @@
-use advent_of_code_2024::solve_all;
+use anyhow::{anyhow, bail, ensure, Result as Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
-fn main() {
-    solve_all();
+use advent_of_code_2024::{data_dir, diagnose_day, solve_all, solve_day, InputSet};
+use advent_of_code_2024::answers::{verify_days, ANSWERS_FILE};
+use advent_of_code_2024::bench::{bench_days, BenchOptions};
+use advent_of_code_2024::day_02_red_nosed_reports::{Direction, SafetyRules};
+use advent_of_code_2024::output::Format;
+use advent_of_code_2024::solution::DiagnoseOptions;
+
+/// Solutions for Advent of Code 2024.
+///
+/// Without a command every solved day is run.
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Output format of the answers.
+    #[arg(short, long, global = true, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Solve on multiple threads, needs the `parallel` feature.
+    ///
+    /// Every day runs at once, and the days that can use multiple threads use them themselves.
+    #[arg(long, global = true)]
+    parallel: bool,
+
+    /// The directory with the puzzle inputs, defaults to $AOC_DATA_DIR or else ./data.
+    #[arg(long, global = true)]
+    data_dir: Option<String>,
+
+    /// Use the inputs in this subdirectory of the data directory, repeat to run multiple sets side by side.
+    #[arg(short = 's', long = "input-set", global = true)]
+    input_sets: Vec<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a single day, optionally limited to one part.
+    Run {
+        /// The day of the puzzle.
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+
+        /// The part of the puzzle, both parts are run when omitted.
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+
+        /// The puzzle input, `-` for stdin, defaults to the file for that day in the data directory.
+        ///
+        /// Gzip and zstd compressed input is decompressed.
+        #[arg(short, long)]
+        input: Option<String>,
+    },
+    /// Measure the parse and solve times of a day, or of every day, over multiple runs.
+    Bench {
+        /// The day of the puzzle, every day is benchmarked when omitted.
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: Option<u8>,
+
+        /// The number of measured runs.
+        #[arg(short, long, default_value_t = 10)]
+        runs: usize,
+
+        /// The number of runs before measuring.
+        #[arg(short, long, default_value_t = 2)]
+        warmup: usize,
+
+        /// Save the medians to this file.
+        #[arg(long)]
+        save: Option<String>,
+
+        /// Compare the medians against a file saved earlier.
+        #[arg(long)]
+        baseline: Option<String>,
+
+        /// Flag a phase when its median is more than this percentage slower than the baseline.
+        #[arg(long, default_value_t = 10.0)]
+        threshold: f64,
+    },
+    /// List how a day arrives at its answers, e.g. the status of every report of day 2.
+    Diagnose {
+        /// The day of the puzzle.
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+
+        /// The puzzle input, `-` for stdin, defaults to the file for that day in the data directory.
+        #[arg(short, long)]
+        input: Option<String>,
+
+        #[command(flatten)]
+        rules: RuleArgs,
+    },
+    /// Solve a day, or every day, and compare the answers to the expected answers.
+    Verify {
+        /// The day of the puzzle, every day is verified when omitted.
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: Option<u8>,
+
+        /// The file with the expected answers.
+        #[arg(short, long, default_value = ANSWERS_FILE)]
+        answers: String,
+    },
+}
+
+/// The safety rules of day 2, every rule that is not given is the rule of part 2.
+#[derive(Args)]
+#[command(next_help_heading = "Day 2 rules")]
+struct RuleArgs {
+    /// The smallest allowed difference between adjacent levels.
+    #[arg(long)]
+    min_step: Option<u8>,
+
+    /// The largest allowed difference between adjacent levels.
+    #[arg(long)]
+    max_step: Option<u8>,
+
+    /// Which way the levels of a safe report go.
+    #[arg(long, value_enum)]
+    direction: Option<DirectionArg>,
+
+    /// The number of levels the Problem Dampener may remove.
+    #[arg(long)]
+    dampener: Option<usize>,
+}
+
+impl RuleArgs {
+    fn is_empty(&self) -> bool {
+        self.min_step.is_none() && self.max_step.is_none() && self.direction.is_none() && self.dampener.is_none()
+    }
+
+    /// The rules of part 2 with the given rules replaced, None when no rule is given.
+    fn rules(&self) -> Option<SafetyRules> {
+        let default = SafetyRules::PART_TWO;
+        (!self.is_empty()).then(|| SafetyRules {
+            min_step: self.min_step.unwrap_or(default.min_step),
+            max_step: self.max_step.unwrap_or(default.max_step),
+            direction: self.direction.map_or(default.direction, Direction::from),
+            dampener: self.dampener.unwrap_or(default.dampener),
+        })
+    }
+}
+
+/// Which way the levels of a safe day 2 report go.
+#[derive(Clone, Copy, ValueEnum)]
+enum DirectionArg {
+    Increasing,
+    Decreasing,
+    /// Either increasing or decreasing, but the same for the whole report.
+    Either,
+}
+
+impl From<DirectionArg> for Direction {
+    fn from(direction: DirectionArg) -> Self {
+        match direction {
+            DirectionArg::Increasing => Direction::Increasing,
+            DirectionArg::Decreasing => Direction::Decreasing,
+            DirectionArg::Either => Direction::Either,
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let sets = InputSet::from_names(&data_dir(cli.data_dir.as_deref()), &cli.input_sets);
+    if sets.len() > 1 && cli.format != Format::Text {
+        bail!("Multiple input sets can only be printed as text");
+    }
+    if cli.parallel && !cfg!(feature = "parallel") {
+        bail!("--parallel needs the `parallel` feature, which this build does not have");
+    }
+
+    match cli.command {
+        Some(Command::Run { day, part, input }) => {
+            ensure_single_input(input.as_deref(), &sets)?;
+            sets.iter().try_for_each(
+                | set | {
+                    if sets.len() > 1 {
+                        println!("Input set {}", set.name);
+                    }
+                    solve_day(day, part, input.as_deref(), cli.format, set, cli.parallel)
+                }
+            )
+        },
+        Some(Command::Bench { day, runs, warmup, save, baseline, threshold }) => {
+            let [inputs] = <[InputSet; 1]>::try_from(sets).map_err(|_| anyhow!("Only a single input set can be benchmarked"))?;
+            bench_days(day, &BenchOptions { inputs, runs, warmup, save, baseline, threshold, parallel: cli.parallel })
+        },
+        Some(Command::Diagnose { day, input, rules }) => {
+            ensure_single_input(input.as_deref(), &sets)?;
+            ensure!(day == 2 || rules.is_empty(), "Only day 2 has safety rules");
+            let options = DiagnoseOptions { safety_rules: rules.rules() };
+            sets.iter().try_for_each(
+                | set | {
+                    if sets.len() > 1 {
+                        println!("Input set {}", set.name);
+                    }
+                    diagnose_day(day, input.as_deref(), set, &options)
+                }
+            )
+        },
+        Some(Command::Verify { day, answers }) => verify_days(day, &answers, &sets, cli.parallel),
+        None => {
+            let failed: usize = sets.iter().map(| set | solve_all(cli.format, cli.parallel, set)).sum();
+            match failed {
+                0 => Ok(()),
+                _ => bail!("{} day(s) could not be solved", failed)
+            }
+        }
+    }
+}
+
+/// An explicit input would be used for every set, so it can only be given with a single input set.
+fn ensure_single_input(input: Option<&str>, sets: &[InputSet]) -> Result<()> {
+    ensure!(input.is_none() || sets.len() == 1, "--input can not be combined with multiple input sets");
+    Ok(())
 }
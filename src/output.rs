@@ -0,0 +1,211 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+use crate::{DayOutcome, DayResult};
+
+const CSV_HEADER: &str = "day,title,part,answer,elapsed_ns";
+
+/// Output format of the answers.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum Format {
+    /// Human-readable text, printed as soon as a day is solved.
+    #[default]
+    Text,
+    /// A JSON array with an object per part.
+    Json,
+    /// CSV with a header and a row per part.
+    Csv,
+}
+
+/// Format a day as human-readable text.
+pub fn format_text(result: &DayResult) -> String {
+    let mut text = format!("Day {}: {}\nRun Time: {:?}\n", result.day, result.title, result.elapsed);
+    for answer in result.solved.answers.iter() {
+        writeln!(text, "{} {}: {}", answer.label, answer.part, answer.value).unwrap();
+    }
+
+    text
+}
+
+/// Format a day as human-readable text, or the reason it could not be solved.
+pub fn format_outcome(outcome: &DayOutcome) -> String {
+    match &outcome.result {
+        Ok(result) => format_text(result),
+        Err(failure) => format!("Day {}: {}\nFailed, {}: {}\n", outcome.day, outcome.title, failure.status(), failure.message()),
+    }
+}
+
+/// Format a table with the status of each day, and the number of days that failed.
+pub fn format_summary(outcomes: &[DayOutcome]) -> String {
+    let width = outcomes.iter().map(| outcome | outcome.title.len()).max().unwrap_or(0).max(5);
+    let mut table = format!("Day  {:<width$}  Status\n", "Title");
+
+    for outcome in outcomes.iter() {
+        write!(table, "{:>3}  {:<width$}  ", outcome.day, outcome.title).unwrap();
+        match &outcome.result {
+            Ok(_) => writeln!(table, "OK").unwrap(),
+            Err(failure) => writeln!(table, "{} ({})", failure.status(), failure.message()).unwrap(),
+        }
+    }
+
+    let failed = outcomes.iter().filter(| outcome | outcome.result.is_err()).count();
+    writeln!(table, "{} of {} days solved", outcomes.len() - failed, outcomes.len()).unwrap();
+    table
+}
+
+/// Format the wall-clock time of the whole run next to the CPU time it took.
+///
+/// The CPU time is summed over every thread, so when solved in parallel it is more than the run time.
+pub fn format_total(wall_clock: Duration, cpu_time: Duration) -> String {
+    format!("Total Run Time: {:?}\nTotal CPU Time: {:?}\n", wall_clock, cpu_time)
+}
+
+/// Format all answers as a JSON array of {day, title, part, answer, elapsed_ns} objects.
+///
+/// The answer is a string, because not every puzzle has a numeric answer.
+pub fn format_json(results: &[DayResult]) -> String {
+    let objects: Vec<String> = results
+        .iter()
+        .flat_map(
+            | result | {
+                result.solved.answers.iter().map(
+                    | answer | format!(
+                        "{{\"day\":{},\"title\":{},\"part\":{},\"answer\":{},\"elapsed_ns\":{}}}",
+                        result.day,
+                        json_string(result.title),
+                        answer.part,
+                        json_string(&answer.value),
+                        answer.elapsed.as_nanos()
+                    )
+                )
+            }
+        )
+        .collect();
+
+    format!("[{}]", objects.join(","))
+}
+
+/// Format all answers as CSV, with a row per part.
+pub fn format_csv(results: &[DayResult]) -> String {
+    let mut csv = format!("{}\n", CSV_HEADER);
+
+    for result in results.iter() {
+        for answer in result.solved.answers.iter() {
+            writeln!(
+                csv, "{},{},{},{},{}",
+                result.day,
+                csv_field(result.title),
+                answer.part,
+                csv_field(&answer.value),
+                answer.elapsed.as_nanos()
+            ).unwrap();
+        }
+    }
+
+    csv
+}
+
+/// Quote and escape a string for JSON.
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+
+    for char in value.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+/// Only quote a CSV field when it contains a separator, quote or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{Answer, Solved};
+    use crate::Failure;
+
+    fn example_results() -> Vec<DayResult> {
+        vec![
+            DayResult {
+                day: 1,
+                title: "Historian \"Hysteria\", Again",
+                elapsed: Duration::from_nanos(3000),
+                solved: Solved {
+                    parse_time: Duration::from_nanos(1000),
+                    answers: vec![
+                        Answer { part: 1, label: "Distance", value: "11".to_string(), elapsed: Duration::from_nanos(1200) },
+                        Answer { part: 2, label: "Score", value: "31".to_string(), elapsed: Duration::from_nanos(800) },
+                    ]
+                }
+            }
+        ]
+    }
+
+    #[test]
+    fn test_format_json() {
+        assert_eq!(
+            format_json(&example_results()),
+            "[{\"day\":1,\"title\":\"Historian \\\"Hysteria\\\", Again\",\"part\":1,\"answer\":\"11\",\"elapsed_ns\":1200},\
+            {\"day\":1,\"title\":\"Historian \\\"Hysteria\\\", Again\",\"part\":2,\"answer\":\"31\",\"elapsed_ns\":800}]"
+        );
+    }
+
+    #[test]
+    fn test_format_total() {
+        assert_eq!(
+            format_total(Duration::from_nanos(4000), Duration::from_nanos(7000)),
+            "Total Run Time: 4µs\nTotal CPU Time: 7µs\n"
+        );
+    }
+
+    #[test]
+    fn test_format_summary() {
+        let outcomes = vec![
+            DayOutcome { day: 1, title: "Historian Hysteria", result: Ok(example_results().remove(0)) },
+            DayOutcome { day: 6, title: "Guard Gallivant", result: Err(Failure::InputMissing("./data/day_06.txt: not found".to_string())) },
+            DayOutcome { day: 16, title: "Reindeer Maze", result: Err(Failure::Panic("index out of bounds".to_string())) },
+        ];
+
+        assert_eq!(
+            format_summary(&outcomes),
+            "Day  Title               Status\n  \
+            1  Historian Hysteria  OK\n  \
+            6  Guard Gallivant     input missing (./data/day_06.txt: not found)\n \
+            16  Reindeer Maze       panic caught (index out of bounds)\n\
+            1 of 3 days solved\n"
+        );
+        assert_eq!(
+            format_outcome(&outcomes[2]),
+            "Day 16: Reindeer Maze\nFailed, panic caught: index out of bounds\n"
+        );
+    }
+
+    #[test]
+    fn test_format_csv() {
+        assert_eq!(
+            format_csv(&example_results()),
+            "day,title,part,answer,elapsed_ns\n\
+            1,\"Historian \"\"Hysteria\"\", Again\",1,11,1200\n\
+            1,\"Historian \"\"Hysteria\"\", Again\",2,31,800\n"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Puzzle input that does not match the expected format.
+///
+/// Lines and columns start counting at 1, like in a text editor.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    /// The offending text, empty at the end of the input.
+    pub text: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, text: &str, expected: &str) -> Self {
+        Self { line, column, text: text.to_string(), expected: expected.to_string() }
+    }
+
+    /// Error for the byte at the given index of the raw input.
+    pub fn at_byte(data: &[u8], index: usize, expected: &str) -> Self {
+        let line_start = data[..index]
+            .iter()
+            .rposition(| &byte | byte == b'\n')
+            .map_or(0, | newline | newline + 1);
+        let line = data[..index].iter().filter(| &&byte | byte == b'\n').count() + 1;
+        let text = data
+            .get(index)
+            .map(| &byte | (byte as char).to_string())
+            .unwrap_or_default();
+
+        Self { line, column: index - line_start + 1, text, expected: expected.to_string() }
+    }
+
+    /// Error for a field of a line, `field` must be a slice of `line`.
+    ///
+    /// The line index starts at 0, as given by `lines().enumerate()`.
+    pub fn in_line(line_index: usize, line: &str, field: &str, expected: &str) -> Self {
+        let column = field.as_ptr() as usize - line.as_ptr() as usize + 1;
+        Self::new(line_index + 1, column, field, expected)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.text.is_empty() {
+            true => write!(f, "line {}, column {}: expected {}, found end of input", self.line, self.column, self.expected),
+            false => write!(f, "line {}, column {}: expected {}, found {:?}", self.line, self.column, self.expected, self.text),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parse a field of a line, reporting the position of the field when it fails.
+pub fn parse_field<T: FromStr>(
+    line_index: usize, line: &str, field: &str, expected: &str
+) -> Result<T, ParseError> {
+    field
+        .parse()
+        .map_err(| _ | ParseError::in_line(line_index, line, field, expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_at_byte() {
+        let data = b"..#\n.x.\r\n";
+        assert_eq!(ParseError::at_byte(data, 5, "'.'"), ParseError::new(2, 2, "x", "'.'"));
+        assert_eq!(ParseError::at_byte(data, 7, "'.'"), ParseError::new(2, 4, "\r", "'.'"));
+        assert_eq!(ParseError::at_byte(data, 9, "'^'"), ParseError::new(3, 1, "", "'^'"));
+    }
+
+    #[test]
+    fn test_parse_field() {
+        let line = "190: 10 1x9";
+        let field = line.split_ascii_whitespace().last().unwrap();
+        assert_eq!(parse_field::<u64>(2, line, field, "a number"), Err(ParseError::new(3, 9, "1x9", "a number")));
+        assert_eq!(
+            ParseError::new(3, 9, "1x9", "a number").to_string(),
+            "line 3, column 9: expected a number, found \"1x9\""
+        );
+    }
+}
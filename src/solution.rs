@@ -0,0 +1,158 @@
+use std::fmt::Display;
+use std::io::BufRead;
+use std::time::{Duration, Instant};
+
+use anyhow::Result as Result;
+
+use crate::day_02_red_nosed_reports::SafetyRules;
+
+/// The solution of a single day of the calendar.
+///
+/// The puzzle input is parsed once and then shared by both parts.
+pub trait Solution {
+    /// The parsed puzzle input.
+    type Input;
+    type PartOne: Display;
+    type PartTwo: Display;
+
+    const DAY: u8;
+    const TITLE: &'static str;
+    /// Description of the answer of each part, e.g. "Sum of Distances".
+    const LABELS: [&'static str; 2];
+    /// Name of the puzzle input file in the data directory.
+    const INPUT_FILE: &'static str;
+
+    fn parse(data: &[u8]) -> Result<Self::Input>;
+
+    /// Parse the input from a reader, by default the whole input is read first.
+    ///
+    /// Days that parse a line at a time override this, so the input does not have to fit in memory.
+    fn parse_reader(reader: &mut dyn BufRead) -> Result<Self::Input> {
+        let mut data: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Self::parse(&data)
+    }
+
+    /// An answer that can not be computed, e.g. because it does not fit in the answer type, is an error.
+    fn part_one(input: &Self::Input) -> Result<Self::PartOne>;
+    fn part_two(input: &Self::Input) -> Result<Self::PartTwo>;
+
+    /// Solve part one, the days that can use multiple threads only do so when `parallel` is set.
+    fn part_one_with(input: &Self::Input, _parallel: bool) -> Result<Self::PartOne> {
+        Self::part_one(input)
+    }
+
+    /// Solve part two, the days that can use multiple threads only do so when `parallel` is set.
+    fn part_two_with(input: &Self::Input, _parallel: bool) -> Result<Self::PartTwo> {
+        Self::part_two(input)
+    }
+
+    /// A human-readable report on the input, for the days that can explain their answers.
+    fn diagnostics(_input: &Self::Input, _options: &DiagnoseOptions) -> Option<String> {
+        None
+    }
+
+    /// Parse the data and solve both parts.
+    fn solve_parts(data: &[u8]) -> Result<(Self::PartOne, Self::PartTwo)> {
+        let input = Self::parse(data)?;
+        Ok((Self::part_one(&input)?, Self::part_two(&input)?))
+    }
+}
+
+/// Options for the days with diagnostics, the rules of the puzzle are used for every option that is None.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DiagnoseOptions {
+    /// The rules to check the reports of day 2 with.
+    pub safety_rules: Option<SafetyRules>,
+}
+
+/// The answer to a single part of a puzzle.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Answer {
+    pub part: u8,
+    pub label: &'static str,
+    pub value: String,
+    /// Time to solve the part, excluding the parsing of the input.
+    pub elapsed: Duration,
+}
+
+/// The answers of a puzzle together with the time spent on parsing the input.
+#[derive(Clone, Debug)]
+pub struct Solved {
+    /// Time to parse the input, including reading it when it is solved from a reader.
+    pub parse_time: Duration,
+    pub answers: Vec<Answer>,
+}
+
+/// Object safe version of a Solution, so all days can be stored in a single table.
+pub trait Puzzle: Sync {
+    fn day(&self) -> u8;
+    fn title(&self) -> &'static str;
+    fn labels(&self) -> [&'static str; 2];
+    fn input_file(&self) -> &'static str;
+
+    /// Parse the data and solve the requested part, or both parts if no part is given.
+    ///
+    /// With `parallel` the days that can use multiple threads solve on the thread pool.
+    fn solve(&self, data: &[u8], part: Option<u8>, parallel: bool) -> Result<Solved>;
+
+    /// Like `solve`, but the input is read while parsing, see `Solution::parse_reader`.
+    fn solve_reader(&self, reader: &mut dyn BufRead, part: Option<u8>, parallel: bool) -> Result<Solved>;
+
+    /// Parse the data and report on it, None when the day has no diagnostics.
+    fn diagnose(&self, data: &[u8], options: &DiagnoseOptions) -> Result<Option<String>>;
+}
+
+impl<S: Solution + Sync> Puzzle for S {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn title(&self) -> &'static str {
+        S::TITLE
+    }
+
+    fn labels(&self) -> [&'static str; 2] {
+        S::LABELS
+    }
+
+    fn input_file(&self) -> &'static str {
+        S::INPUT_FILE
+    }
+
+    fn diagnose(&self, data: &[u8], options: &DiagnoseOptions) -> Result<Option<String>> {
+        Ok(S::diagnostics(&S::parse(data)?, options))
+    }
+
+    fn solve(&self, data: &[u8], part: Option<u8>, parallel: bool) -> Result<Solved> {
+        let timer = Instant::now();
+        let input = S::parse(data)?;
+
+        solve_parsed::<S>(&input, timer.elapsed(), part, parallel)
+    }
+
+    fn solve_reader(&self, reader: &mut dyn BufRead, part: Option<u8>, parallel: bool) -> Result<Solved> {
+        let timer = Instant::now();
+        let input = S::parse_reader(reader)?;
+
+        solve_parsed::<S>(&input, timer.elapsed(), part, parallel)
+    }
+}
+
+/// Solve the requested part of the parsed input, or both parts if no part is given.
+fn solve_parsed<S: Solution>(input: &S::Input, parse_time: Duration, part: Option<u8>, parallel: bool) -> Result<Solved> {
+    let mut answers: Vec<Answer> = Vec::with_capacity(2);
+
+    if part.is_none_or(| part | part == 1) {
+        let timer = Instant::now();
+        let value = S::part_one_with(input, parallel)?.to_string();
+        answers.push(Answer { part: 1, label: S::LABELS[0], value, elapsed: timer.elapsed() });
+    }
+    if part.is_none_or(| part | part == 2) {
+        let timer = Instant::now();
+        let value = S::part_two_with(input, parallel)?.to_string();
+        answers.push(Answer { part: 2, label: S::LABELS[1], value, elapsed: timer.elapsed() });
+    }
+
+    Ok(Solved { parse_time, answers })
+}
@@ -0,0 +1,16 @@
+/// Deterministic random numbers for the randomised tests, the same on every run and without a dependency.
+///
+/// A 64-bit linear congruential generator, the high bits are used because the low bits have short periods.
+pub struct Random(u64);
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// A number in 0..bound.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.0 = self.0.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        (self.0 >> 33) % bound
+    }
+}